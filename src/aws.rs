@@ -0,0 +1,239 @@
+use crate::environment::parameter_or_environment;
+use crate::timecode::civil_date;
+use hmac::{Hmac, Mac, NewMac};
+use mcai_worker_sdk::MessageError;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SIGNING_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Credentials and region of the AWS requests
+#[derive(Debug)]
+pub struct AwsCredentials {
+  access_key_id: String,
+  secret_access_key: String,
+  session_token: Option<String>,
+  pub region: String,
+}
+
+fn hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Hexadecimal SHA-256 of a request payload
+pub fn payload_hash(payload: &[u8]) -> String {
+  hex(&Sha256::digest(payload))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+  let mut mac = Hmac::<Sha256>::new_varkey(key).expect("HMAC accepts any key size");
+  mac.update(data.as_bytes());
+  mac.finalize().into_bytes().to_vec()
+}
+
+/// Request date and time, formatted as YYYYMMDD'T'HHMMSS'Z'
+fn amz_date() -> String {
+  let seconds = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_secs())
+    .unwrap_or(0);
+  let (year, month, day) = civil_date((seconds / 86_400) as i64);
+
+  format!(
+    "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+    year,
+    month,
+    day,
+    (seconds / 3600) % 24,
+    (seconds / 60) % 60,
+    seconds % 60
+  )
+}
+
+impl AwsCredentials {
+  /// Credentials of the parameters, or of the standard AWS environment variables
+  pub fn new(
+    access_key_id: &Option<String>,
+    secret_access_key: &Option<String>,
+    region: &Option<String>,
+  ) -> Result<Self, MessageError> {
+    let access_key_id = parameter_or_environment(access_key_id, &["AWS_ACCESS_KEY_ID"])
+      .ok_or_else(|| {
+        MessageError::ParameterValueError("Missing AWS access key identifier".to_string())
+      })?;
+    let secret_access_key = parameter_or_environment(secret_access_key, &["AWS_SECRET_ACCESS_KEY"])
+      .ok_or_else(|| {
+        MessageError::ParameterValueError("Missing AWS secret access key".to_string())
+      })?;
+    let region = parameter_or_environment(region, &["AWS_REGION", "AWS_DEFAULT_REGION"])
+      .unwrap_or_else(|| "us-east-1".to_string());
+
+    Ok(AwsCredentials {
+      access_key_id,
+      secret_access_key,
+      session_token: env::var("AWS_SESSION_TOKEN").ok(),
+      region,
+    })
+  }
+
+  /// Headers signing a request without query string with AWS Signature Version 4,
+  /// to set on the request along with the Host header
+  pub fn signed_headers(
+    &self,
+    service: &str,
+    method: &str,
+    host: &str,
+    path: &str,
+    payload_hash: &str,
+  ) -> Vec<(&'static str, String)> {
+    self.signed_headers_at(&amz_date(), service, method, host, path, payload_hash)
+  }
+
+  /// Key deriving the signatures of the day, region and service
+  fn signing_key(&self, date: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", self.secret_access_key).as_bytes(), date);
+    let key = hmac_sha256(&key, &self.region);
+    let key = hmac_sha256(&key, service);
+    hmac_sha256(&key, "aws4_request")
+  }
+
+  /// Signature of the canonical request sent at the date and time
+  fn signature(&self, date_time: &str, service: &str, canonical_request: &str) -> String {
+    let date = &date_time[..8];
+    let string_to_sign = format!(
+      "{}\n{}\n{}/{}/{}/aws4_request\n{}",
+      SIGNING_ALGORITHM,
+      date_time,
+      date,
+      self.region,
+      service,
+      hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+    hex(&hmac_sha256(
+      &self.signing_key(date, service),
+      &string_to_sign,
+    ))
+  }
+
+  /// Signed headers of the request sent at the date and time
+  fn signed_headers_at(
+    &self,
+    date_time: &str,
+    service: &str,
+    method: &str,
+    host: &str,
+    path: &str,
+    payload_hash: &str,
+  ) -> Vec<(&'static str, String)> {
+    let date = &date_time[..8];
+    let scope = format!("{}/{}/{}/aws4_request", date, self.region, service);
+
+    let mut headers = vec![
+      ("host", host.to_string()),
+      ("x-amz-content-sha256", payload_hash.to_string()),
+      ("x-amz-date", date_time.to_string()),
+    ];
+    if let Some(session_token) = &self.session_token {
+      headers.push(("x-amz-security-token", session_token.clone()));
+    }
+
+    let canonical_headers: String = headers
+      .iter()
+      .map(|(name, value)| format!("{}:{}\n", name, value))
+      .collect();
+    let signed_headers = headers
+      .iter()
+      .map(|(name, _)| *name)
+      .collect::<Vec<&str>>()
+      .join(";");
+    let canonical_request = format!(
+      "{}\n{}\n\n{}\n{}\n{}",
+      method, path, canonical_headers, signed_headers, payload_hash
+    );
+    let signature = self.signature(date_time, service, &canonical_request);
+
+    headers.remove(0);
+    headers.push((
+      "Authorization",
+      format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        SIGNING_ALGORITHM, self.access_key_id, scope, signed_headers, signature
+      ),
+    ));
+    headers
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Credentials of the examples of the AWS Signature Version 4 documentation
+  fn example_credentials() -> AwsCredentials {
+    AwsCredentials {
+      access_key_id: "AKIDEXAMPLE".to_string(),
+      secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+      session_token: None,
+      region: "us-east-1".to_string(),
+    }
+  }
+
+  #[test]
+  fn signing_key_of_the_documentation() {
+    let key = example_credentials().signing_key("20120215", "iam");
+
+    assert_eq!(
+      hex(&key),
+      "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+    );
+  }
+
+  #[test]
+  fn signature_of_the_get_vanilla_test_suite_request() {
+    let canonical_request = "GET\n/\n\n\
+      host:example.amazonaws.com\n\
+      x-amz-date:20150830T123600Z\n\n\
+      host;x-amz-date\n\
+      e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let signature =
+      example_credentials().signature("20150830T123600Z", "service", canonical_request);
+
+    assert_eq!(
+      signature,
+      "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+    );
+  }
+
+  #[test]
+  fn signed_headers_of_an_empty_request() {
+    let empty_payload = payload_hash(b"");
+    let headers = example_credentials().signed_headers_at(
+      "20150830T123600Z",
+      "service",
+      "GET",
+      "example.amazonaws.com",
+      "/",
+      &empty_payload,
+    );
+
+    assert_eq!(
+      empty_payload,
+      "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+      headers,
+      vec![
+        ("x-amz-content-sha256", empty_payload.clone()),
+        ("x-amz-date", "20150830T123600Z".to_string()),
+        (
+          "Authorization",
+          "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+           SignedHeaders=host;x-amz-content-sha256;x-amz-date, \
+           Signature=726c5c4879a6b4ccbbd3b24edbd6b8826d34f87450fbbf4e85546fc7ba9c1642"
+            .to_string()
+        ),
+      ]
+    );
+  }
+}
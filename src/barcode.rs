@@ -0,0 +1,125 @@
+use crate::ocr::FrameBuffer;
+use crate::region::{Rectangle, SourceMapping};
+use mcai_worker_sdk::{JsonSchema, MessageError};
+#[cfg(feature = "barcodes")]
+use rxing::{helpers::detect_multiple_in_luma, Exceptions};
+
+/// Decoding of the QR codes and barcodes shown in the frames
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+#[cfg_attr(not(feature = "barcodes"), allow(dead_code))]
+pub struct BarcodeParameters {
+  /// Formats to report, like qr_code, data_matrix or ean_13 (default: all the supported formats)
+  formats: Option<Vec<String>>,
+}
+
+/// QR code or barcode decoded in the frame
+#[derive(Clone, Debug, JsonSchema, Serialize)]
+pub struct Barcode {
+  /// Symbology of the code, like qr_code or ean_13
+  pub format: String,
+  pub payload: String,
+  pub bounding_box: Rectangle,
+}
+
+impl Barcode {
+  pub fn into_source_frame(self, mapping: &SourceMapping) -> Self {
+    Barcode {
+      bounding_box: mapping.rectangle(self.bounding_box),
+      ..self
+    }
+  }
+}
+
+/// Reader of the codes, decoding the luminance of the frames
+#[derive(Debug)]
+pub struct BarcodeReader {
+  #[cfg(feature = "barcodes")]
+  formats: Option<Vec<String>>,
+}
+
+#[cfg(feature = "barcodes")]
+fn luma(buffer: &FrameBuffer) -> Vec<u8> {
+  let bytes_per_pixel = buffer.bytes_per_pixel as usize;
+  let mut luma = Vec::with_capacity((buffer.width * buffer.height) as usize);
+  for y in 0..buffer.height as usize {
+    let line = &buffer.data[y * buffer.bytes_per_line as usize..];
+    for x in 0..buffer.width as usize {
+      let pixel = &line[x * bytes_per_pixel..];
+      luma.push(if bytes_per_pixel >= 3 {
+        ((299 * pixel[0] as u32 + 587 * pixel[1] as u32 + 114 * pixel[2] as u32) / 1000) as u8
+      } else {
+        pixel[0]
+      });
+    }
+  }
+  luma
+}
+
+#[cfg(feature = "barcodes")]
+impl BarcodeReader {
+  pub fn new(parameters: &BarcodeParameters) -> Result<Self, MessageError> {
+    Ok(BarcodeReader {
+      formats: parameters.formats.as_ref().map(|formats| {
+        formats
+          .iter()
+          .map(|format| format.to_lowercase().replace('-', "_"))
+          .collect()
+      }),
+    })
+  }
+
+  /// Codes of the frame, located in the processed image
+  pub fn decode(&self, buffer: &FrameBuffer) -> Result<Vec<Barcode>, MessageError> {
+    let results =
+      match detect_multiple_in_luma(luma(buffer), buffer.width as u32, buffer.height as u32) {
+        Ok(results) => results,
+        Err(Exceptions::NotFoundException(_)) => return Ok(vec![]),
+        Err(error) => {
+          return Err(MessageError::RuntimeError(format!(
+            "Unable to decode the barcodes: {}",
+            error
+          )))
+        }
+      };
+
+    Ok(
+      results
+        .iter()
+        .map(|result| {
+          let bounding_box = Rectangle::bounding_polygon(
+            result
+              .getPoints()
+              .iter()
+              .map(|point| (point.x as f64, point.y as f64)),
+          );
+
+          Barcode {
+            format: format!("{:?}", result.getBarcodeFormat()).to_lowercase(),
+            payload: result.getText().to_string(),
+            bounding_box,
+          }
+        })
+        .filter(|barcode| {
+          self
+            .formats
+            .as_ref()
+            .map(|formats| formats.contains(&barcode.format))
+            .unwrap_or(true)
+        })
+        .collect(),
+    )
+  }
+}
+
+#[cfg(not(feature = "barcodes"))]
+impl BarcodeReader {
+  pub fn new(_parameters: &BarcodeParameters) -> Result<Self, MessageError> {
+    Err(MessageError::ParameterValueError(
+      "Unable to decode the barcodes: the worker is built without the barcodes feature".to_string(),
+    ))
+  }
+
+  pub fn decode(&self, _buffer: &FrameBuffer) -> Result<Vec<Barcode>, MessageError> {
+    Ok(vec![])
+  }
+}
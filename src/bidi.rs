@@ -0,0 +1,49 @@
+use mcai_worker_sdk::JsonSchema;
+use unicode_bidi::{bidi_class, BidiClass};
+use unicode_normalization::UnicodeNormalization;
+
+/// Writing direction of a line, from its first strongly directional character
+#[derive(Clone, Copy, Debug, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextDirection {
+  Ltr,
+  Rtl,
+}
+
+impl Default for TextDirection {
+  fn default() -> Self {
+    TextDirection::Ltr
+  }
+}
+
+impl TextDirection {
+  /// Paragraph direction of the text, as resolved by the rules P2 and P3 of the Unicode bidi algorithm
+  pub fn of(text: &str) -> Self {
+    text
+      .chars()
+      .find_map(|character| match bidi_class(character) {
+        BidiClass::L => Some(TextDirection::Ltr),
+        BidiClass::R | BidiClass::AL => Some(TextDirection::Rtl),
+        _ => None,
+      })
+      .unwrap_or_default()
+  }
+}
+
+/// Explicit directional formatting characters, inserted by Tesseract around the numbers of RTL text
+fn is_directional_formatting(character: char) -> bool {
+  matches!(
+    character,
+    '\u{200e}' | '\u{200f}' | '\u{061c}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+  )
+}
+
+/// Text in logical order, without the directional formatting characters and composed in NFC,
+/// so that the renderers apply the bidi algorithm on the plain text
+pub fn normalize(text: &str) -> String {
+  text
+    .chars()
+    .filter(|character| !is_directional_formatting(*character))
+    .nfc()
+    .collect()
+}
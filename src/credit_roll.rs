@@ -0,0 +1,81 @@
+use crate::layout::Line;
+use crate::ocr::FrameBuffer;
+use crate::stitching::{Composite, Stitcher};
+use crate::text_event::normalize;
+use crate::timecode::format_milliseconds;
+use mcai_worker_sdk::JsonSchema;
+use std::collections::HashSet;
+
+/// Stitching of the scrolling end credits into a tall image recognised at once
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct CreditRollParameters {
+  /// Maximum scroll between two processed frames, in pixels (default: half of the frame height)
+  max_scroll: Option<u32>,
+  /// Mean difference of the row luminance above which the frames are not part of the same roll,
+  /// from 0 to 255 (default: 24)
+  max_difference: Option<f64>,
+  /// Height of the stitched image recognised before stitching the next one, in pixels
+  /// (default: 8000)
+  max_height: Option<u32>,
+}
+
+/// Credit line read in the roll, with the time its line entered the frame
+#[derive(Debug, Serialize)]
+pub struct Credit {
+  pub text: String,
+  pub frame: u32,
+  pub timestamp_ms: u64,
+  /// Approximate timecode, the scroll being estimated between the processed frames
+  pub timecode: String,
+}
+
+/// Stitcher of the credits, sending each credit line once
+#[derive(Debug)]
+pub struct CreditRoll {
+  stitcher: Stitcher,
+  /// Normalized credits already sent, the stitched images overlapping
+  seen: HashSet<String>,
+}
+
+impl CreditRoll {
+  pub fn new(parameters: &CreditRollParameters) -> Self {
+    CreditRoll {
+      stitcher: Stitcher::new(
+        parameters.max_scroll,
+        parameters.max_difference.unwrap_or(24.0),
+        parameters.max_height.unwrap_or(8000),
+      ),
+      seen: HashSet::new(),
+    }
+  }
+
+  /// Stitch the frame below the previous ones,
+  /// returning the stitched image to recognise when it is complete
+  pub fn add(&mut self, buffer: &FrameBuffer, frame: u32, timestamp_ms: u64) -> Option<Composite> {
+    self.stitcher.add(buffer, frame, timestamp_ms)
+  }
+
+  /// Stitched image of the frames not recognised yet, at the end of the job
+  pub fn finish(&mut self) -> Option<Composite> {
+    self.stitcher.finish()
+  }
+
+  /// Credits of the lines recognised in the stitched image, skipping the ones already sent
+  pub fn credits(&mut self, composite: &Composite, lines: &[Line]) -> Vec<Credit> {
+    let mut credits = vec![];
+    for line in lines {
+      let text = normalize(&line.text);
+      if text.is_empty() || !self.seen.insert(text.to_lowercase()) {
+        continue;
+      }
+      let (frame, timestamp_ms) = composite.row(line.bounding_box.top);
+      credits.push(Credit {
+        text,
+        frame,
+        timestamp_ms,
+        timecode: format_milliseconds(timestamp_ms, '.'),
+      });
+    }
+    credits
+  }
+}
@@ -0,0 +1,49 @@
+use crate::s3::{S3Client, S3Location, S3Parameters};
+use mcai_worker_sdk::{info, MessageError};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+/// Local file receiving the results, uploaded at the end of the job for the remote destinations
+#[derive(Debug, Default)]
+pub struct Destination {
+  pub path: String,
+  upload: Option<(S3Client, S3Location)>,
+}
+
+impl Destination {
+  pub fn new(destination_path: String, s3: Option<S3Parameters>) -> Result<Self, MessageError> {
+    let location = match S3Location::parse(&destination_path) {
+      Some(location) => location,
+      None => {
+        return Ok(Destination {
+          path: destination_path,
+          upload: None,
+        })
+      }
+    };
+
+    let client = S3Client::new(&s3.unwrap_or_default())?;
+    let file_name = location.key.rsplit('/').next().unwrap_or_default();
+    let path = env::temp_dir().join(format!("{}-{}", process::id(), file_name));
+
+    Ok(Destination {
+      path: path.to_string_lossy().to_string(),
+      upload: Some((client, location)),
+    })
+  }
+
+  /// Upload the local file to its remote destination
+  pub fn finish(&self) -> Result<(), MessageError> {
+    if let Some((client, location)) = &self.upload {
+      if !Path::new(&self.path).exists() {
+        return Ok(());
+      }
+      info!("Upload {} to {}", self.path, location);
+      client.put_object(location, Path::new(&self.path))?;
+      let _ = fs::remove_file(&self.path);
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,10 @@
+use std::env;
+
+/// Value of the parameter, or else of the first set environment variable
+pub fn parameter_or_environment(value: &Option<String>, variables: &[&str]) -> Option<String> {
+  value.clone().or_else(|| {
+    variables
+      .iter()
+      .find_map(|variable| env::var(variable).ok())
+  })
+}
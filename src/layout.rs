@@ -0,0 +1,162 @@
+use crate::bidi::TextDirection;
+use crate::region::{Point, Rectangle, SourceMapping};
+use mcai_worker_sdk::JsonSchema;
+
+/// Word recognised by Tesseract, located in the processed image
+#[derive(Clone, Debug, JsonSchema, Serialize)]
+pub struct Word {
+  pub text: String,
+  pub bounding_box: Rectangle,
+  pub confidence: f32,
+  pub block: u32,
+  pub paragraph: u32,
+  pub line: u32,
+}
+
+impl Word {
+  pub fn into_source_frame(self, mapping: &SourceMapping) -> Self {
+    Word {
+      bounding_box: mapping.rectangle(self.bounding_box),
+      ..self
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, JsonSchema, Serialize)]
+pub struct Baseline {
+  pub start: Point,
+  pub end: Point,
+}
+
+/// Text line recognised by Tesseract, located in the processed image
+#[derive(Clone, Debug, JsonSchema, Serialize)]
+pub struct Line {
+  pub text: String,
+  pub bounding_box: Rectangle,
+  pub baseline: Option<Baseline>,
+  pub confidence: f32,
+  /// Writing direction, the text and the words of the line being in logical order
+  pub direction: TextDirection,
+}
+
+impl Line {
+  pub fn into_source_frame(self, mapping: &SourceMapping) -> Self {
+    Line {
+      bounding_box: mapping.rectangle(self.bounding_box),
+      baseline: self.baseline.map(|baseline| Baseline {
+        start: mapping.point(baseline.start),
+        end: mapping.point(baseline.end),
+      }),
+      ..self
+    }
+  }
+}
+
+/// Text line of the layout hierarchy, with its words
+#[derive(Clone, Debug, JsonSchema, Serialize)]
+pub struct TextLine {
+  #[serde(flatten)]
+  pub line: Line,
+  pub words: Vec<Word>,
+}
+
+#[derive(Clone, Debug, JsonSchema, Serialize)]
+pub struct Paragraph {
+  pub bounding_box: Rectangle,
+  pub lines: Vec<TextLine>,
+}
+
+/// Block of text, the root of the layout hierarchy in reading order
+#[derive(Clone, Debug, JsonSchema, Serialize)]
+pub struct Block {
+  pub bounding_box: Rectangle,
+  pub paragraphs: Vec<Paragraph>,
+}
+
+impl Block {
+  pub fn into_source_frame(self, mapping: &SourceMapping) -> Self {
+    Block {
+      bounding_box: mapping.rectangle(self.bounding_box),
+      paragraphs: self
+        .paragraphs
+        .into_iter()
+        .map(|paragraph| Paragraph {
+          bounding_box: mapping.rectangle(paragraph.bounding_box),
+          lines: paragraph
+            .lines
+            .into_iter()
+            .map(|text_line| TextLine {
+              line: text_line.line.into_source_frame(mapping),
+              words: text_line
+                .words
+                .into_iter()
+                .map(|word| word.into_source_frame(mapping))
+                .collect(),
+            })
+            .collect(),
+        })
+        .collect(),
+    }
+  }
+}
+
+/// Tesseract TSV level of the word rows
+const WORD_LEVEL: &str = "5";
+
+/// Parse the words of the Tesseract TSV output:
+/// `level page_num block_num par_num line_num word_num left top width height conf text`
+pub fn parse_tsv(tsv: &str) -> Vec<Word> {
+  tsv
+    .lines()
+    .filter_map(|row| {
+      let columns: Vec<&str> = row.splitn(12, '\t').collect();
+      if columns.len() != 12 || columns[0] != WORD_LEVEL {
+        return None;
+      }
+
+      let text = columns[11].trim();
+      if text.is_empty() {
+        return None;
+      }
+
+      Some(Word {
+        text: text.to_string(),
+        bounding_box: Rectangle {
+          left: columns[6].parse().ok()?,
+          top: columns[7].parse().ok()?,
+          width: columns[8].parse().ok()?,
+          height: columns[9].parse().ok()?,
+        },
+        confidence: columns[10].parse().ok()?,
+        block: columns[2].parse().ok()?,
+        paragraph: columns[3].parse().ok()?,
+        line: columns[4].parse().ok()?,
+      })
+    })
+    .collect()
+}
+
+/// Mean confidence of the words, between 0 and 100
+pub fn mean_confidence(words: &[Word]) -> Option<f32> {
+  if words.is_empty() {
+    return None;
+  }
+  Some(words.iter().map(|word| word.confidence).sum::<f32>() / words.len() as f32)
+}
+
+/// Text of the words, one line per text line
+pub fn words_text(words: &[Word]) -> String {
+  let mut text = String::new();
+  let mut previous: Option<&Word> = None;
+
+  for word in words {
+    if let Some(previous) = previous {
+      let same_line = (previous.block, previous.paragraph, previous.line)
+        == (word.block, word.paragraph, word.line);
+      text.push(if same_line { ' ' } else { '\n' });
+    }
+    text.push_str(&word.text);
+    previous = Some(word);
+  }
+  text
+}
@@ -0,0 +1,177 @@
+use crate::layout::Line;
+use crate::text_event::{levenshtein_distance, normalize, Votes};
+use crate::timecode::format_milliseconds;
+use mcai_worker_sdk::{JsonSchema, RegionOfInterest};
+
+/// Separators between the name and the title of a single line strap, like "Jane Doe, Reporter"
+const SEPARATORS: [&str; 4] = [" | ", " - ", " – ", ", "];
+
+/// Extraction of the names and titles shown in the lower thirds
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct LowerThirdParameters {
+  /// Maximum number of edits between the names of consecutive frames of a strap (default: 2)
+  merge_distance: Option<usize>,
+}
+
+impl LowerThirdParameters {
+  /// Region of interest preset on the bottom third of the frame
+  pub fn region_of_interest(frame_height: u32) -> RegionOfInterest {
+    RegionOfInterest {
+      top: Some(frame_height * 2 / 3),
+      left: None,
+      right: None,
+      bottom: None,
+      width: None,
+      height: None,
+    }
+  }
+}
+
+/// Strap shown over consecutive processed frames
+#[derive(Debug, Serialize)]
+pub struct LowerThird {
+  pub name: String,
+  /// Role or function of the person, empty when the strap only shows the name
+  pub title: String,
+  #[serde(rename = "in")]
+  pub in_timecode: String,
+  #[serde(rename = "out")]
+  pub out_timecode: String,
+  pub in_ms: u64,
+  pub out_ms: u64,
+  pub start_frame: u32,
+  pub end_frame: u32,
+}
+
+#[derive(Debug)]
+struct Strap {
+  name: String,
+  names: Votes,
+  titles: Votes,
+  start_frame: u32,
+  end_frame: u32,
+  in_ms: u64,
+  out_ms: u64,
+}
+
+impl Strap {
+  fn into_lower_third(self) -> LowerThird {
+    LowerThird {
+      name: self.names.majority().unwrap_or_default().to_string(),
+      title: self.titles.majority().unwrap_or_default().to_string(),
+      in_timecode: format_milliseconds(self.in_ms, '.'),
+      out_timecode: format_milliseconds(self.out_ms, '.'),
+      in_ms: self.in_ms,
+      out_ms: self.out_ms,
+      start_frame: self.start_frame,
+      end_frame: self.end_frame,
+    }
+  }
+}
+
+/// Name and title of the strap lines: the tallest line is the name and the others the title,
+/// a single line being split on its first separator
+fn name_and_title(lines: &[Line]) -> Option<(String, String)> {
+  let lines: Vec<&Line> = lines
+    .iter()
+    .filter(|line| !line.text.trim().is_empty())
+    .collect();
+
+  match lines.as_slice() {
+    [] => None,
+    [line] => {
+      let text = normalize(&line.text);
+      let split = SEPARATORS
+        .iter()
+        .filter_map(|separator| text.find(separator).map(|index| (index, separator.len())))
+        .min();
+      Some(match split {
+        Some((index, length)) => (
+          text[..index].trim().to_string(),
+          text[index + length..].trim().to_string(),
+        ),
+        None => (text, String::new()),
+      })
+    }
+    lines => {
+      let name_index = lines.iter().enumerate().fold(0, |tallest, (index, line)| {
+        if line.bounding_box.height > lines[tallest].bounding_box.height {
+          index
+        } else {
+          tallest
+        }
+      });
+      let title = lines
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != name_index)
+        .map(|(_, line)| normalize(&line.text))
+        .collect::<Vec<String>>()
+        .join(" ");
+      Some((normalize(&lines[name_index].text), title))
+    }
+  }
+}
+
+/// Group the frames showing the same strap, closing a strap when it disappears
+#[derive(Debug)]
+pub struct LowerThirdTracker {
+  merge_distance: usize,
+  current: Option<Strap>,
+}
+
+impl LowerThirdTracker {
+  pub fn new(parameters: &LowerThirdParameters) -> Self {
+    LowerThirdTracker {
+      merge_distance: parameters.merge_distance.unwrap_or(2),
+      current: None,
+    }
+  }
+
+  /// Add the lines of a processed frame lasting from `start_ms` to `end_ms`,
+  /// returning the strap closed by the frame
+  pub fn add(
+    &mut self,
+    frame: u32,
+    start_ms: u64,
+    end_ms: u64,
+    lines: &[Line],
+  ) -> Option<LowerThird> {
+    let (name, title) = match name_and_title(lines) {
+      Some(name_and_title) => name_and_title,
+      None => return self.finish(),
+    };
+
+    if let Some(strap) = &mut self.current {
+      if levenshtein_distance(&strap.name, &name) <= self.merge_distance {
+        strap.names.add(&name);
+        strap.titles.add(&title);
+        strap.name = name;
+        strap.end_frame = frame;
+        strap.out_ms = end_ms;
+        return None;
+      }
+    }
+
+    let mut names = Votes::default();
+    names.add(&name);
+    let mut titles = Votes::default();
+    titles.add(&title);
+    let closed = self.current.take().map(Strap::into_lower_third);
+    self.current = Some(Strap {
+      name,
+      names,
+      titles,
+      start_frame: frame,
+      end_frame: frame,
+      in_ms: start_ms,
+      out_ms: end_ms,
+    });
+    closed
+  }
+
+  /// Close the strap still shown at the end of the job
+  pub fn finish(&mut self) -> Option<LowerThird> {
+    self.current.take().map(Strap::into_lower_third)
+  }
+}
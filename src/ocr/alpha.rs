@@ -0,0 +1,68 @@
+use super::{FrameBuffer, OwnedFrame};
+use mcai_worker_sdk::MessageError;
+
+/// Compositing of the frames with an alpha channel, like the rendered caption overlays,
+/// onto a background color
+#[derive(Debug)]
+pub struct AlphaCompositing {
+  background: [u8; 3],
+  grayscale: bool,
+}
+
+impl AlphaCompositing {
+  /// Compositing onto the background color, like #000000, into gray or RGB frames
+  pub fn new(background_color: Option<&str>, grayscale: bool) -> Result<Self, MessageError> {
+    let background = match background_color {
+      Some(color) => parse_color(color).ok_or_else(|| {
+        MessageError::ParameterValueError(format!(
+          "Invalid background color {}, expected #RRGGBB",
+          color
+        ))
+      })?,
+      None => [0, 0, 0],
+    };
+
+    Ok(AlphaCompositing {
+      background,
+      grayscale,
+    })
+  }
+
+  /// Frame composited onto the background, from the RGBA frame
+  pub fn apply(&self, buffer: &FrameBuffer) -> OwnedFrame {
+    let bytes_per_pixel = if self.grayscale { 1 } else { 3 };
+    let mut data =
+      Vec::with_capacity((buffer.width * buffer.height) as usize * bytes_per_pixel as usize);
+    for y in 0..buffer.height as usize {
+      let line = &buffer.data[y * buffer.bytes_per_line as usize..];
+      for pixel in line.chunks_exact(4).take(buffer.width as usize) {
+        let alpha = pixel[3] as u32;
+        let mut composited = [0u8; 3];
+        for (channel, value) in composited.iter_mut().enumerate() {
+          *value = ((pixel[channel] as u32 * alpha
+            + self.background[channel] as u32 * (255 - alpha)
+            + 127)
+            / 255) as u8;
+        }
+        if self.grayscale {
+          data.push(
+            ((299 * composited[0] as u32 + 587 * composited[1] as u32 + 114 * composited[2] as u32)
+              / 1000) as u8,
+          );
+        } else {
+          data.extend_from_slice(&composited);
+        }
+      }
+    }
+    OwnedFrame::new(data, buffer.width, buffer.height, bytes_per_pixel)
+  }
+}
+
+fn parse_color(color: &str) -> Option<[u8; 3]> {
+  let hex = color.strip_prefix('#')?;
+  if hex.len() != 6 || !hex.is_ascii() {
+    return None;
+  }
+  let component = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+  Some([component(0)?, component(2)?, component(4)?])
+}
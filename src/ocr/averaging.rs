@@ -0,0 +1,92 @@
+use super::{FrameBuffer, OwnedFrame};
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use std::collections::VecDeque;
+
+/// Average of the consecutive frames of a static region, suppressing the compression noise of
+/// the heavily compressed proxies
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct TemporalAverageParameters {
+  /// Number of consecutive frames averaged (default: 5)
+  frames: Option<usize>,
+  /// Mean difference of the pixels with the previous frame from which the region is not static,
+  /// the average restarting with the frame, from 0 to 255 (default: 8)
+  max_difference: Option<f64>,
+}
+
+/// Pixels of the last static frames, summed
+#[derive(Debug)]
+pub struct TemporalAverage {
+  frames: usize,
+  max_difference: f64,
+  window: VecDeque<Vec<u8>>,
+  sums: Vec<u32>,
+}
+
+impl TemporalAverage {
+  pub fn new(parameters: &TemporalAverageParameters) -> Result<Self, MessageError> {
+    let frames = parameters.frames.unwrap_or(5);
+    if frames == 0 {
+      return Err(MessageError::ParameterValueError(
+        "The temporal average needs at least one frame".to_string(),
+      ));
+    }
+
+    Ok(TemporalAverage {
+      frames,
+      max_difference: parameters.max_difference.unwrap_or(8.0),
+      window: VecDeque::with_capacity(frames),
+      sums: vec![],
+    })
+  }
+
+  /// Average of the frame with the previous static ones
+  pub fn add(&mut self, buffer: &FrameBuffer) -> OwnedFrame {
+    let line_size = (buffer.width * buffer.bytes_per_pixel) as usize;
+    let mut data = Vec::with_capacity(line_size * buffer.height as usize);
+    for y in 0..buffer.height as usize {
+      let offset = y * buffer.bytes_per_line as usize;
+      data.extend_from_slice(&buffer.data[offset..offset + line_size]);
+    }
+
+    let moved = match self.window.back() {
+      Some(previous) if previous.len() == data.len() && !data.is_empty() => {
+        previous
+          .iter()
+          .zip(&data)
+          .map(|(previous, value)| (*previous as i32 - *value as i32).unsigned_abs() as u64)
+          .sum::<u64>() as f64
+          / data.len() as f64
+          > self.max_difference
+      }
+      _ => true,
+    };
+    if moved {
+      self.window.clear();
+      self.sums = vec![0; data.len()];
+    }
+    if self.window.len() == self.frames {
+      if let Some(oldest) = self.window.pop_front() {
+        for (sum, value) in self.sums.iter_mut().zip(oldest) {
+          *sum -= value as u32;
+        }
+      }
+    }
+    for (sum, value) in self.sums.iter_mut().zip(&data) {
+      *sum += *value as u32;
+    }
+    self.window.push_back(data);
+
+    let count = self.window.len() as u32;
+    let averaged = self
+      .sums
+      .iter()
+      .map(|sum| ((sum + count / 2) / count) as u8)
+      .collect();
+    OwnedFrame::new(
+      averaged,
+      buffer.width,
+      buffer.height,
+      buffer.bytes_per_pixel,
+    )
+  }
+}
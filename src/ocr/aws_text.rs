@@ -0,0 +1,352 @@
+use super::cloud::{encode_jpeg, send_with_retries, RateLimiter};
+use super::{FrameBuffer, OcrEngine, OcrSettings, Recognition};
+use crate::aws::{payload_hash, AwsCredentials};
+use crate::bidi::TextDirection;
+use crate::layout::{Block, Line, Paragraph, TextLine, Word};
+use crate::region::Rectangle;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use std::collections::HashMap;
+
+/// AWS service detecting the text
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AwsTextService {
+  /// Rekognition DetectText, for the text in scenes and overlays
+  Rekognition,
+  /// Textract DetectDocumentText, for dense document-like text
+  Textract,
+}
+
+impl Default for AwsTextService {
+  fn default() -> Self {
+    AwsTextService::Rekognition
+  }
+}
+
+impl AwsTextService {
+  fn name(self) -> &'static str {
+    match self {
+      AwsTextService::Rekognition => "rekognition",
+      AwsTextService::Textract => "textract",
+    }
+  }
+
+  fn target(self) -> &'static str {
+    match self {
+      AwsTextService::Rekognition => "RekognitionService.DetectText",
+      AwsTextService::Textract => "Textract.DetectDocumentText",
+    }
+  }
+}
+
+/// AWS text detection, with the credentials of the account
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+pub struct AwsTextParameters {
+  /// Service detecting the text: rekognition or textract (default: rekognition)
+  service: Option<AwsTextService>,
+  /// Access key identifier (default: AWS_ACCESS_KEY_ID environment variable)
+  access_key_id: Option<String>,
+  /// Secret access key (default: AWS_SECRET_ACCESS_KEY environment variable)
+  secret_access_key: Option<String>,
+  /// Region of the service (default: AWS_REGION environment variable, or us-east-1)
+  region: Option<String>,
+  /// Endpoint of the service (default: AWS endpoint of the service in the region)
+  endpoint: Option<String>,
+  /// Quality of the JPEG frames sent to AWS, between 1 and 100 (default: 90)
+  jpeg_quality: Option<u8>,
+  /// Maximum number of requests per minute (default: unlimited)
+  requests_per_minute: Option<u32>,
+  /// Number of retries of a failed request (default: 3)
+  retries: Option<u32>,
+}
+
+/// OCR engine sending each frame to AWS Rekognition or Textract
+#[derive(Debug)]
+pub struct AwsTextEngine {
+  parameters: AwsTextParameters,
+  service: AwsTextService,
+  credentials: AwsCredentials,
+  endpoint: String,
+  rate_limiter: RateLimiter,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct BoundingBox {
+  width: f64,
+  height: f64,
+  left: f64,
+  top: f64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct Geometry {
+  bounding_box: BoundingBox,
+}
+
+/// Text detected by Rekognition
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct TextDetection {
+  detected_text: String,
+  #[serde(rename = "Type")]
+  kind: String,
+  id: u32,
+  parent_id: Option<u32>,
+  confidence: f32,
+  geometry: Geometry,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct DetectTextResponse {
+  text_detections: Vec<TextDetection>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct Relationship {
+  #[serde(rename = "Type")]
+  kind: String,
+  ids: Vec<String>,
+}
+
+/// Page, line or word detected by Textract
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct TextractBlock {
+  block_type: String,
+  id: String,
+  text: String,
+  confidence: f32,
+  geometry: Geometry,
+  relationships: Vec<Relationship>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct DetectDocumentTextResponse {
+  blocks: Vec<TextractBlock>,
+}
+
+/// Detected text, with its bounding box relative to the frame size
+struct DetectedText<'a> {
+  text: &'a str,
+  confidence: f32,
+  bounding_box: &'a BoundingBox,
+}
+
+impl TextDetection {
+  fn detected_text(&self) -> DetectedText<'_> {
+    DetectedText {
+      text: &self.detected_text,
+      confidence: self.confidence,
+      bounding_box: &self.geometry.bounding_box,
+    }
+  }
+}
+
+impl TextractBlock {
+  fn detected_text(&self) -> DetectedText<'_> {
+    DetectedText {
+      text: &self.text,
+      confidence: self.confidence,
+      bounding_box: &self.geometry.bounding_box,
+    }
+  }
+}
+
+impl AwsTextEngine {
+  pub fn new(parameters: &AwsTextParameters) -> Result<Self, MessageError> {
+    let service = parameters.service.unwrap_or_default();
+    let credentials = AwsCredentials::new(
+      &parameters.access_key_id,
+      &parameters.secret_access_key,
+      &parameters.region,
+    )?;
+    let endpoint = parameters
+      .endpoint
+      .clone()
+      .unwrap_or_else(|| {
+        format!(
+          "https://{}.{}.amazonaws.com",
+          service.name(),
+          credentials.region
+        )
+      })
+      .trim_end_matches('/')
+      .to_string();
+
+    Ok(AwsTextEngine {
+      parameters: parameters.clone(),
+      service,
+      credentials,
+      endpoint,
+      rate_limiter: RateLimiter::new(parameters.requests_per_minute),
+    })
+  }
+
+  fn detect<T: serde::de::DeserializeOwned>(&mut self, jpeg: &[u8]) -> Result<T, MessageError> {
+    let image = json!({ "Bytes": base64::encode(jpeg) });
+    let body = match self.service {
+      AwsTextService::Rekognition => json!({ "Image": image }),
+      AwsTextService::Textract => json!({ "Document": image }),
+    }
+    .to_string();
+    let host = self.endpoint.splitn(2, "://").last().unwrap_or_default();
+    let hash = payload_hash(body.as_bytes());
+    let service = format!("AWS {}", self.service.name());
+
+    self.rate_limiter.wait();
+    let response = send_with_retries(&service, self.parameters.retries.unwrap_or(3), || {
+      let mut request = ureq::post(&format!("{}/", self.endpoint));
+      for (name, value) in
+        self
+          .credentials
+          .signed_headers(self.service.name(), "POST", host, "/", &hash)
+      {
+        request.set(name, &value);
+      }
+      request
+        .set("Content-Type", "application/x-amz-json-1.1")
+        .set("X-Amz-Target", self.service.target())
+        .send_string(&body)
+    })?;
+
+    response.into_json_deserialize::<T>().map_err(|error| {
+      MessageError::RuntimeError(format!("Invalid {} response: {}", service, error))
+    })
+  }
+}
+
+fn rectangle(bounding_box: &BoundingBox, buffer: &FrameBuffer) -> Rectangle {
+  let (width, height) = (buffer.width as f64, buffer.height as f64);
+  Rectangle {
+    left: (bounding_box.left * width).max(0.0).round() as u32,
+    top: (bounding_box.top * height).max(0.0).round() as u32,
+    width: (bounding_box.width * width).max(0.0).round() as u32,
+    height: (bounding_box.height * height).max(0.0).round() as u32,
+  }
+}
+
+impl OcrEngine for AwsTextEngine {
+  fn recognise(
+    &mut self,
+    buffer: &FrameBuffer,
+    settings: &OcrSettings,
+    _page: i32,
+  ) -> Result<Recognition, MessageError> {
+    let jpeg = encode_jpeg(buffer, self.parameters.jpeg_quality.unwrap_or(90))?;
+
+    let rekognition_response;
+    let textract_response;
+    // lines with their words, in reading order
+    let detected_lines: Vec<(DetectedText, Vec<DetectedText>)> = match self.service {
+      AwsTextService::Rekognition => {
+        rekognition_response = self.detect::<DetectTextResponse>(&jpeg)?;
+        let detections = &rekognition_response.text_detections;
+        detections
+          .iter()
+          .filter(|detection| detection.kind == "LINE")
+          .map(|line| {
+            let words = detections
+              .iter()
+              .filter(|word| word.kind == "WORD" && word.parent_id == Some(line.id))
+              .map(TextDetection::detected_text)
+              .collect();
+            (line.detected_text(), words)
+          })
+          .collect()
+      }
+      AwsTextService::Textract => {
+        textract_response = self.detect::<DetectDocumentTextResponse>(&jpeg)?;
+        let blocks: HashMap<&str, &TextractBlock> = textract_response
+          .blocks
+          .iter()
+          .map(|block| (block.id.as_str(), block))
+          .collect();
+        textract_response
+          .blocks
+          .iter()
+          .filter(|block| block.block_type == "LINE")
+          .map(|line| {
+            let words = line
+              .relationships
+              .iter()
+              .filter(|relationship| relationship.kind == "CHILD")
+              .flat_map(|relationship| relationship.ids.iter())
+              .filter_map(|id| blocks.get(id.as_str()))
+              .filter(|block| block.block_type == "WORD")
+              .map(|block| block.detected_text())
+              .collect();
+            (line.detected_text(), words)
+          })
+          .collect()
+      }
+    };
+
+    let mut words = vec![];
+    let mut text_lines = vec![];
+    for (line, line_words) in detected_lines {
+      let line_number = text_lines.len() as u32 + 1;
+      let line_words: Vec<Word> = line_words
+        .iter()
+        .map(|word| Word {
+          text: word.text.to_string(),
+          bounding_box: rectangle(word.bounding_box, buffer),
+          confidence: word.confidence,
+          block: 1,
+          paragraph: 1,
+          line: line_number,
+        })
+        .collect();
+      words.extend(line_words.iter().cloned());
+      text_lines.push(TextLine {
+        line: Line {
+          text: line.text.to_string(),
+          bounding_box: rectangle(line.bounding_box, buffer),
+          baseline: None,
+          confidence: line.confidence,
+          direction: TextDirection::of(line.text),
+        },
+        words: line_words,
+      });
+    }
+
+    let text = text_lines
+      .iter()
+      .map(|text_line| text_line.line.text.as_str())
+      .collect::<Vec<&str>>()
+      .join("\n");
+    let lines = text_lines
+      .iter()
+      .map(|text_line| text_line.line.clone())
+      .collect();
+    let blocks = match text_lines.first() {
+      Some(first) if settings.layout => {
+        let bounding_box = text_lines
+          .iter()
+          .fold(first.line.bounding_box, |bounding_box, text_line| {
+            bounding_box.union(&text_line.line.bounding_box)
+          });
+        vec![Block {
+          bounding_box,
+          paragraphs: vec![Paragraph {
+            bounding_box,
+            lines: text_lines,
+          }],
+        }]
+      }
+      _ => vec![],
+    };
+
+    Ok(Recognition {
+      text,
+      hocr: None,
+      words,
+      lines,
+      blocks,
+    })
+  }
+}
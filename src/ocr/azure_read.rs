@@ -0,0 +1,282 @@
+use super::cloud::{encode_jpeg, send_with_retries, RateLimiter};
+use super::{FrameBuffer, OcrEngine, OcrSettings, Recognition};
+use crate::bidi::TextDirection;
+use crate::environment::parameter_or_environment;
+use crate::layout::{Block, Line, Paragraph, TextLine, Word};
+use crate::region::Rectangle;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const SERVICE: &str = "Azure Read";
+
+/// Azure Computer Vision Read API, with the credentials of the resource
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+pub struct AzureReadParameters {
+  /// Endpoint of the Computer Vision resource, like https://<name>.cognitiveservices.azure.com
+  /// (default: AZURE_VISION_ENDPOINT environment variable)
+  endpoint: Option<String>,
+  /// Subscription key of the resource (default: AZURE_VISION_KEY environment variable)
+  subscription_key: Option<String>,
+  /// Language of the text as a BCP-47 code, like en or fr (default: detected by Azure)
+  language: Option<String>,
+  /// Version of the Read model, like latest or 2022-04-30 (default: latest)
+  model_version: Option<String>,
+  /// Quality of the JPEG frames sent to Azure, between 1 and 100 (default: 90)
+  jpeg_quality: Option<u8>,
+  /// Maximum number of analyses per minute (default: unlimited)
+  requests_per_minute: Option<u32>,
+  /// Number of retries of a failed request (default: 3)
+  retries: Option<u32>,
+  /// Interval between the polls of the analysis result, in milliseconds (default: 500)
+  poll_interval: Option<u64>,
+  /// Maximum duration of an analysis, in seconds (default: 60)
+  timeout: Option<u64>,
+}
+
+/// OCR engine sending each frame to the Azure Read API, then polling the analysis result
+#[derive(Debug)]
+pub struct AzureReadEngine {
+  parameters: AzureReadParameters,
+  endpoint: String,
+  subscription_key: String,
+  rate_limiter: RateLimiter,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ReadWord {
+  /// Corners of the quadrangle, as x and y coordinates in pixels
+  bounding_box: Vec<f64>,
+  text: String,
+  confidence: f32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ReadLine {
+  bounding_box: Vec<f64>,
+  text: String,
+  words: Vec<ReadWord>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ReadResult {
+  lines: Vec<ReadLine>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct AnalyzeResult {
+  read_results: Vec<ReadResult>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ReadError {
+  message: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ReadOperationResult {
+  status: String,
+  analyze_result: Option<AnalyzeResult>,
+  error: Option<ReadError>,
+}
+
+fn quadrangle_bounding_box(coordinates: &[f64]) -> Rectangle {
+  Rectangle::bounding_polygon(
+    coordinates
+      .chunks_exact(2)
+      .map(|point| (point[0], point[1])),
+  )
+}
+
+impl AzureReadEngine {
+  pub fn new(parameters: &AzureReadParameters) -> Result<Self, MessageError> {
+    let endpoint = parameter_or_environment(&parameters.endpoint, &["AZURE_VISION_ENDPOINT"])
+      .ok_or_else(|| {
+        MessageError::ParameterValueError("Missing Azure Computer Vision endpoint".to_string())
+      })?;
+    let subscription_key =
+      parameter_or_environment(&parameters.subscription_key, &["AZURE_VISION_KEY"]).ok_or_else(
+        || {
+          MessageError::ParameterValueError(
+            "Missing Azure Computer Vision subscription key".to_string(),
+          )
+        },
+      )?;
+
+    Ok(AzureReadEngine {
+      parameters: parameters.clone(),
+      endpoint: endpoint.trim_end_matches('/').to_string(),
+      subscription_key,
+      rate_limiter: RateLimiter::new(parameters.requests_per_minute),
+    })
+  }
+
+  /// Submit the frame to the Read API, returning the location of the analysis
+  fn analyze(&mut self, jpeg: &[u8]) -> Result<String, MessageError> {
+    let url = format!("{}/vision/v3.2/read/analyze", self.endpoint);
+    let retries = self.parameters.retries.unwrap_or(3);
+
+    self.rate_limiter.wait();
+    let response = send_with_retries(SERVICE, retries, || {
+      let mut request = ureq::post(&url);
+      request
+        .set("Ocp-Apim-Subscription-Key", &self.subscription_key)
+        .set("Content-Type", "application/octet-stream");
+      if let Some(language) = &self.parameters.language {
+        request.query("language", language);
+      }
+      if let Some(model_version) = &self.parameters.model_version {
+        request.query("model-version", model_version);
+      }
+      request.send_bytes(jpeg)
+    })?;
+
+    response
+      .header("Operation-Location")
+      .map(str::to_string)
+      .ok_or_else(|| {
+        MessageError::RuntimeError(format!(
+          "Missing operation location in the {} response",
+          SERVICE
+        ))
+      })
+  }
+
+  /// Poll the analysis until it succeeds, fails or times out
+  fn read_result(&self, operation_location: &str) -> Result<AnalyzeResult, MessageError> {
+    let retries = self.parameters.retries.unwrap_or(3);
+    let poll_interval = Duration::from_millis(self.parameters.poll_interval.unwrap_or(500));
+    let timeout = Duration::from_secs(self.parameters.timeout.unwrap_or(60));
+    let start = Instant::now();
+
+    loop {
+      let response = send_with_retries(SERVICE, retries, || {
+        ureq::get(operation_location)
+          .set("Ocp-Apim-Subscription-Key", &self.subscription_key)
+          .call()
+      })?;
+      let result = response
+        .into_json_deserialize::<ReadOperationResult>()
+        .map_err(|error| {
+          MessageError::RuntimeError(format!("Invalid {} response: {}", SERVICE, error))
+        })?;
+
+      match result.status.as_str() {
+        "succeeded" => return Ok(result.analyze_result.unwrap_or_default()),
+        "failed" => {
+          return Err(MessageError::RuntimeError(format!(
+            "{} is unable to analyze the frame: {}",
+            SERVICE,
+            result.error.map(|error| error.message).unwrap_or_default()
+          )))
+        }
+        _ => {}
+      }
+
+      if start.elapsed() >= timeout {
+        return Err(MessageError::RuntimeError(format!(
+          "{} analysis still {} after {} seconds",
+          SERVICE,
+          result.status,
+          timeout.as_secs()
+        )));
+      }
+      thread::sleep(poll_interval);
+    }
+  }
+}
+
+impl OcrEngine for AzureReadEngine {
+  fn recognise(
+    &mut self,
+    buffer: &FrameBuffer,
+    settings: &OcrSettings,
+    _page: i32,
+  ) -> Result<Recognition, MessageError> {
+    let jpeg = encode_jpeg(buffer, self.parameters.jpeg_quality.unwrap_or(90))?;
+    let operation_location = self.analyze(&jpeg)?;
+    let analyze_result = self.read_result(&operation_location)?;
+
+    let mut text_lines = vec![];
+    for read_line in analyze_result
+      .read_results
+      .into_iter()
+      .flat_map(|read_result| read_result.lines.into_iter())
+    {
+      let line_number = text_lines.len() as u32 + 1;
+      let words: Vec<Word> = read_line
+        .words
+        .iter()
+        .map(|read_word| Word {
+          text: read_word.text.clone(),
+          bounding_box: quadrangle_bounding_box(&read_word.bounding_box),
+          confidence: read_word.confidence * 100.0,
+          block: 1,
+          paragraph: 1,
+          line: line_number,
+        })
+        .collect();
+      let confidence = if words.is_empty() {
+        0.0
+      } else {
+        words.iter().map(|word| word.confidence).sum::<f32>() / words.len() as f32
+      };
+
+      text_lines.push(TextLine {
+        line: Line {
+          direction: TextDirection::of(&read_line.text),
+          bounding_box: quadrangle_bounding_box(&read_line.bounding_box),
+          text: read_line.text,
+          baseline: None,
+          confidence,
+        },
+        words,
+      });
+    }
+
+    let text = text_lines
+      .iter()
+      .map(|text_line| text_line.line.text.as_str())
+      .collect::<Vec<&str>>()
+      .join("\n");
+    let words = text_lines
+      .iter()
+      .flat_map(|text_line| text_line.words.iter().cloned())
+      .collect();
+    let lines = text_lines
+      .iter()
+      .map(|text_line| text_line.line.clone())
+      .collect();
+    let blocks = match text_lines.first() {
+      Some(first) if settings.layout => {
+        let bounding_box = text_lines
+          .iter()
+          .fold(first.line.bounding_box, |bounding_box, text_line| {
+            bounding_box.union(&text_line.line.bounding_box)
+          });
+        vec![Block {
+          bounding_box,
+          paragraphs: vec![Paragraph {
+            bounding_box,
+            lines: text_lines,
+          }],
+        }]
+      }
+      _ => vec![],
+    };
+
+    Ok(Recognition {
+      text,
+      hocr: None,
+      words,
+      lines,
+      blocks,
+    })
+  }
+}
@@ -0,0 +1,104 @@
+use super::FrameBuffer;
+use jpeg_encoder::{ColorType, Encoder};
+use mcai_worker_sdk::{warn, MessageError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Spacing of the requests sent to a cloud OCR service
+#[derive(Debug)]
+pub struct RateLimiter {
+  interval: Option<Duration>,
+  last_request: Option<Instant>,
+}
+
+impl RateLimiter {
+  pub fn new(requests_per_minute: Option<u32>) -> Self {
+    RateLimiter {
+      interval: requests_per_minute
+        .filter(|requests| *requests > 0)
+        .map(|requests| Duration::from_secs(60) / requests),
+      last_request: None,
+    }
+  }
+
+  /// Wait until the next request can be sent
+  pub fn wait(&mut self) {
+    if let (Some(interval), Some(last_request)) = (self.interval, self.last_request) {
+      let elapsed = last_request.elapsed();
+      if elapsed < interval {
+        thread::sleep(interval - elapsed);
+      }
+    }
+    self.last_request = Some(Instant::now());
+  }
+}
+
+/// Frame encoded in JPEG, as sent to the cloud OCR services
+pub fn encode_jpeg(buffer: &FrameBuffer, quality: u8) -> Result<Vec<u8>, MessageError> {
+  let color_type = match buffer.bytes_per_pixel {
+    1 => ColorType::Luma,
+    3 => ColorType::Rgb,
+    4 => ColorType::Rgba,
+    bytes_per_pixel => {
+      return Err(MessageError::RuntimeError(format!(
+        "Unable to encode frames with {} bytes per pixel in JPEG",
+        bytes_per_pixel
+      )))
+    }
+  };
+
+  // drop the padding at the end of the lines
+  let line_size = (buffer.width * buffer.bytes_per_pixel) as usize;
+  let mut data = Vec::with_capacity(line_size * buffer.height as usize);
+  for line in 0..buffer.height as usize {
+    let start = line * buffer.bytes_per_line as usize;
+    data.extend_from_slice(&buffer.data[start..start + line_size]);
+  }
+
+  let mut jpeg = vec![];
+  Encoder::new(&mut jpeg, quality.clamp(1, 100))
+    .encode(&data, buffer.width as u16, buffer.height as u16, color_type)
+    .map_err(|error| {
+      MessageError::RuntimeError(format!("Unable to encode the frame in JPEG: {}", error))
+    })?;
+  Ok(jpeg)
+}
+
+/// Send a request, retrying the connection, rate limit and server errors with an exponential backoff
+pub fn send_with_retries(
+  service: &str,
+  retries: u32,
+  send: impl Fn() -> ureq::Response,
+) -> Result<ureq::Response, MessageError> {
+  let mut attempt = 0;
+  loop {
+    let response = send();
+    let error = match response.synthetic_error() {
+      Some(error) => error.to_string(),
+      None if response.status() == 429 || response.server_error() => {
+        response.status_line().to_string()
+      }
+      None if response.error() => {
+        let status_line = response.status_line().to_string();
+        return Err(MessageError::RuntimeError(format!(
+          "{} rejected the request: {} {}",
+          service,
+          status_line,
+          response.into_string().unwrap_or_default()
+        )));
+      }
+      None => return Ok(response),
+    };
+
+    if attempt >= retries {
+      return Err(MessageError::RuntimeError(format!(
+        "Unable to reach {}: {}",
+        service, error
+      )));
+    }
+
+    warn!("Unable to reach {} ({}), retrying", service, error);
+    thread::sleep(Duration::from_secs(1 << attempt.min(6)));
+    attempt += 1;
+  }
+}
@@ -0,0 +1,333 @@
+#[cfg(feature = "gpu")]
+use super::device::{load_session, run_session};
+use super::{Device, FrameBuffer};
+use crate::region::Rectangle;
+#[cfg(feature = "onnx")]
+use mcai_worker_sdk::warn;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+#[cfg(feature = "onnx")]
+use tract_onnx::prelude::*;
+
+#[cfg(feature = "onnx")]
+const DEFAULT_DETECTION_SIZE: u32 = 960;
+#[cfg(feature = "onnx")]
+const DEFAULT_DETECTION_THRESHOLD: f32 = 0.3;
+#[cfg(feature = "onnx")]
+const DEFAULT_BOX_THRESHOLD: f32 = 0.6;
+
+/// Expansion of the detected regions, the DB models predicting shrunk text regions
+#[cfg(feature = "onnx")]
+const UNCLIP_RATIO: f32 = 1.5;
+/// Smallest detected region kept, in pixels of the probability map
+#[cfg(feature = "onnx")]
+const MIN_REGION_SIZE: usize = 3;
+/// Normalization of the detection model inputs, in BGR order
+#[cfg(feature = "onnx")]
+const DETECTION_MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+#[cfg(feature = "onnx")]
+const DETECTION_STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+/// Neural text detection, locating the text lines before their recognition
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+#[cfg_attr(not(feature = "onnx"), allow(dead_code))]
+pub struct TextDetectionParameters {
+  /// Path of the ONNX detection model, predicting a text probability map like DB, EAST or CRAFT
+  pub(super) model: String,
+  /// Longest side of the frames given to the model, in pixels (default: 960)
+  pub(super) size: Option<u32>,
+  /// Probability of the text pixels on the map, between 0 and 1 (default: 0.3)
+  pub(super) threshold: Option<f32>,
+  /// Minimum mean probability of a detected text region, between 0 and 1 (default: 0.6)
+  pub(super) box_threshold: Option<f32>,
+}
+
+/// Model ready to run, optimized by tract on the CPU or loaded by ONNX Runtime on the GPU
+#[cfg(feature = "onnx")]
+pub(super) enum Plan {
+  Tract(TypedSimplePlan<TypedModel>),
+  #[cfg(feature = "gpu")]
+  OnnxRuntime(ort::Session),
+}
+
+/// Text line detected in the frame, with its probability
+#[cfg_attr(not(feature = "onnx"), allow(dead_code))]
+pub struct TextRegion {
+  pub area: Rectangle,
+  pub score: f32,
+}
+
+/// Text detection model, optimized for the size of the job frames once known
+pub struct TextDetector {
+  #[cfg(feature = "onnx")]
+  parameters: TextDetectionParameters,
+  #[cfg(feature = "onnx")]
+  device: Device,
+  #[cfg(feature = "onnx")]
+  plan: Option<(usize, usize, Plan)>,
+}
+
+impl std::fmt::Debug for TextDetector {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    f.debug_struct("TextDetector").finish()
+  }
+}
+
+/// Model of the path for inputs of the shape, on the CPU when the device is not available
+#[cfg(feature = "onnx")]
+pub(super) fn load_model(
+  path: &str,
+  shape: [usize; 4],
+  device: Device,
+) -> Result<Plan, MessageError> {
+  if device == Device::Cuda {
+    #[cfg(feature = "gpu")]
+    match load_session(path) {
+      Ok(session) => return Ok(Plan::OnnxRuntime(session)),
+      Err(error) => warn!("Run the ONNX model {} on the CPU: {:?}", path, error),
+    }
+    #[cfg(not(feature = "gpu"))]
+    warn!(
+      "Run the ONNX model {} on the CPU: the worker is built without the gpu feature",
+      path
+    );
+  }
+
+  tract_onnx::onnx()
+    .model_for_path(path)
+    .and_then(|model| model.with_input_fact(0, f32::fact(shape).into()))
+    .and_then(|model| model.into_optimized())
+    .and_then(|model| model.into_runnable())
+    .map(Plan::Tract)
+    .map_err(|error| {
+      MessageError::RuntimeError(format!("Unable to load the ONNX model {}: {}", path, error))
+    })
+}
+
+#[cfg(feature = "onnx")]
+pub(super) fn run_model(
+  plan: &Plan,
+  input: Tensor,
+) -> Result<(Vec<usize>, Vec<f32>), MessageError> {
+  let plan = match plan {
+    Plan::Tract(plan) => plan,
+    #[cfg(feature = "gpu")]
+    Plan::OnnxRuntime(session) => {
+      let shape = input.shape().to_vec();
+      let input = input
+        .as_slice::<f32>()
+        .map_err(|error| {
+          MessageError::RuntimeError(format!("Invalid ONNX model input: {}", error))
+        })?
+        .to_vec();
+      return run_session(session, &shape, input);
+    }
+  };
+  let outputs = plan.run(tvec!(input.into())).map_err(|error| {
+    MessageError::RuntimeError(format!("Unable to run the ONNX model: {}", error))
+  })?;
+  let output = outputs[0].to_array_view::<f32>().map_err(|error| {
+    MessageError::RuntimeError(format!("Unexpected ONNX model output: {}", error))
+  })?;
+  Ok((output.shape().to_vec(), output.iter().cloned().collect()))
+}
+
+/// Channel of a pixel, in the BGR order of the PaddleOCR models
+#[cfg(feature = "onnx")]
+fn bgr_value(buffer: &FrameBuffer, x: usize, y: usize, channel: usize) -> f32 {
+  let offset = y * buffer.bytes_per_line as usize + x * buffer.bytes_per_pixel as usize;
+  let channel = if buffer.bytes_per_pixel >= 3 {
+    2 - channel
+  } else {
+    0
+  };
+  buffer.data[offset + channel] as f32 / 255.0
+}
+
+/// Area of the frame resized with the nearest pixels into a `1x3xHxW` tensor,
+/// the columns after `width` being padded with zeros
+#[cfg(feature = "onnx")]
+pub(super) fn area_tensor(
+  buffer: &FrameBuffer,
+  area: &Rectangle,
+  width: usize,
+  shape: (usize, usize),
+  normalize: impl Fn(usize, f32) -> f32,
+) -> Tensor {
+  let (input_height, input_width) = shape;
+  tract_ndarray::Array4::from_shape_fn((1, 3, input_height, input_width), |(_, c, y, x)| {
+    if x >= width {
+      return 0.0;
+    }
+    let source_x =
+      area.left as usize + (x * area.width as usize / width).min(area.width as usize - 1);
+    let source_y =
+      area.top as usize + (y * area.height as usize / input_height).min(area.height as usize - 1);
+    normalize(c, bgr_value(buffer, source_x, source_y, c))
+  })
+  .into()
+}
+
+/// Side resized to the closest multiple of 32, as required by the detection models
+#[cfg(feature = "onnx")]
+fn detection_side(side: usize, scale: f32) -> usize {
+  ((((side as f32 * scale) / 32.0).round() as usize) * 32).max(32)
+}
+
+#[cfg(feature = "onnx")]
+impl TextDetector {
+  pub fn new(parameters: &TextDetectionParameters, device: Device) -> Result<Self, MessageError> {
+    Ok(TextDetector {
+      parameters: parameters.clone(),
+      device,
+      plan: None,
+    })
+  }
+
+  /// Text lines of the frame, in reading order
+  pub fn detect(&mut self, buffer: &FrameBuffer) -> Result<Vec<TextRegion>, MessageError> {
+    let (width, height) = (buffer.width as usize, buffer.height as usize);
+    let detection_size = self.parameters.size.unwrap_or(DEFAULT_DETECTION_SIZE) as f32;
+    let scale = (detection_size / width.max(height) as f32).min(1.0);
+    let (input_width, input_height) = (detection_side(width, scale), detection_side(height, scale));
+
+    let plan = match self.plan.take() {
+      Some((plan_width, plan_height, plan))
+        if plan_width == input_width && plan_height == input_height =>
+      {
+        plan
+      }
+      _ => load_model(
+        &self.parameters.model,
+        [1, 3, input_height, input_width],
+        self.device,
+      )?,
+    };
+
+    let frame = Rectangle {
+      left: 0,
+      top: 0,
+      width: width as u32,
+      height: height as u32,
+    };
+    let input = area_tensor(
+      buffer,
+      &frame,
+      input_width,
+      (input_height, input_width),
+      |channel, value| (value - DETECTION_MEAN[channel]) / DETECTION_STD[channel],
+    );
+    let (shape, probabilities) = run_model(&plan, input)?;
+    self.plan = Some((input_width, input_height, plan));
+
+    let (map_height, map_width, channels) = match shape.as_slice() {
+      // channel last scores, like the region and affinity scores of CRAFT
+      [_, map_height, map_width, channels] if *channels <= 2 => {
+        (*map_height, *map_width, *channels)
+      }
+      [.., map_height, map_width] => (*map_height, *map_width, 1),
+      _ => return Ok(vec![]),
+    };
+    let probabilities: Vec<f32> = probabilities.into_iter().step_by(channels).collect();
+    let threshold = self
+      .parameters
+      .threshold
+      .unwrap_or(DEFAULT_DETECTION_THRESHOLD);
+    let box_threshold = self
+      .parameters
+      .box_threshold
+      .unwrap_or(DEFAULT_BOX_THRESHOLD);
+    let (scale_x, scale_y) = (
+      width as f32 / map_width as f32,
+      height as f32 / map_height as f32,
+    );
+
+    let mut visited = vec![false; map_width * map_height];
+    let mut regions = vec![];
+    for start in 0..map_width * map_height {
+      if visited[start] || probabilities[start] <= threshold {
+        continue;
+      }
+
+      // flood fill of the connected text pixels
+      let (mut left, mut top, mut right, mut bottom) = (map_width, map_height, 0, 0);
+      let (mut sum, mut count) = (0.0, 0);
+      let mut stack = vec![start];
+      visited[start] = true;
+      while let Some(index) = stack.pop() {
+        let (x, y) = (index % map_width, index / map_width);
+        left = left.min(x);
+        top = top.min(y);
+        right = right.max(x);
+        bottom = bottom.max(y);
+        sum += probabilities[index];
+        count += 1;
+
+        let neighbours = [
+          (x > 0, index.wrapping_sub(1)),
+          (x + 1 < map_width, index + 1),
+          (y > 0, index.wrapping_sub(map_width)),
+          (y + 1 < map_height, index + map_width),
+        ];
+        for (exists, neighbour) in neighbours.iter() {
+          if *exists && !visited[*neighbour] && probabilities[*neighbour] > threshold {
+            visited[*neighbour] = true;
+            stack.push(*neighbour);
+          }
+        }
+      }
+
+      let (region_width, region_height) = (right - left + 1, bottom - top + 1);
+      let score = sum / count as f32;
+      if region_width < MIN_REGION_SIZE || region_height < MIN_REGION_SIZE || score < box_threshold
+      {
+        continue;
+      }
+
+      let distance = (region_width * region_height) as f32 * UNCLIP_RATIO
+        / (2 * (region_width + region_height)) as f32;
+      let left = ((left as f32 - distance) * scale_x).max(0.0) as u32;
+      let top = ((top as f32 - distance) * scale_y).max(0.0) as u32;
+      let right = (((right + 1) as f32 + distance) * scale_x).min(width as f32) as u32;
+      let bottom = (((bottom + 1) as f32 + distance) * scale_y).min(height as f32) as u32;
+      if right <= left || bottom <= top {
+        continue;
+      }
+
+      regions.push(TextRegion {
+        area: Rectangle {
+          left,
+          top,
+          width: right - left,
+          height: bottom - top,
+        },
+        score,
+      });
+    }
+
+    // top to bottom, then left to right for the regions on the same line
+    regions.sort_by_key(|region| (region.area.top, region.area.left));
+    for index in 1..regions.len() {
+      let (previous, current) = (&regions[index - 1].area, &regions[index].area);
+      if current.top.saturating_sub(previous.top) < previous.height.min(current.height) / 2
+        && current.left < previous.left
+      {
+        regions.swap(index - 1, index);
+      }
+    }
+    Ok(regions)
+  }
+}
+
+#[cfg(not(feature = "onnx"))]
+impl TextDetector {
+  pub fn new(parameters: &TextDetectionParameters, _device: Device) -> Result<Self, MessageError> {
+    Err(MessageError::ParameterValueError(format!(
+      "Unable to load the ONNX model {}: the worker is built without the onnx feature",
+      parameters.model
+    )))
+  }
+
+  pub fn detect(&mut self, _buffer: &FrameBuffer) -> Result<Vec<TextRegion>, MessageError> {
+    Ok(vec![])
+  }
+}
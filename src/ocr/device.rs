@@ -0,0 +1,69 @@
+use mcai_worker_sdk::JsonSchema;
+#[cfg(feature = "gpu")]
+use mcai_worker_sdk::MessageError;
+#[cfg(feature = "gpu")]
+use ort::{
+  tensor::OrtOwnedTensor, Environment, ExecutionProvider, GraphOptimizationLevel, Session,
+  SessionBuilder, Value,
+};
+
+/// Device running the ONNX models of the onnx engine and of the text detection
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Device {
+  Cpu,
+  /// NVIDIA GPU with ONNX Runtime, requires the gpu feature
+  Cuda,
+}
+
+impl Default for Device {
+  fn default() -> Self {
+    Device::Cpu
+  }
+}
+
+/// Model loaded by ONNX Runtime on the GPU
+#[cfg(feature = "gpu")]
+pub fn load_session(path: &str) -> Result<Session, MessageError> {
+  let provider = ExecutionProvider::CUDA(Default::default());
+  if !provider.is_available() {
+    return Err(MessageError::RuntimeError(
+      "the CUDA execution provider is not available".to_string(),
+    ));
+  }
+
+  let environment = Environment::builder()
+    .with_name("rs_text_recognition_worker")
+    .with_execution_providers([provider])
+    .build()
+    .map_err(|error| MessageError::RuntimeError(error.to_string()))?
+    .into_arc();
+  SessionBuilder::new(&environment)
+    .and_then(|builder| builder.with_optimization_level(GraphOptimizationLevel::Level3))
+    .and_then(|builder| builder.with_model_from_file(path))
+    .map_err(|error| MessageError::RuntimeError(error.to_string()))
+}
+
+/// Output of the model for the input tensor, with its shape
+#[cfg(feature = "gpu")]
+pub fn run_session(
+  session: &Session,
+  shape: &[usize],
+  input: Vec<f32>,
+) -> Result<(Vec<usize>, Vec<f32>), MessageError> {
+  let input = ndarray::CowArray::from(
+    ndarray::ArrayD::from_shape_vec(ndarray::IxDyn(shape), input).map_err(|error| {
+      MessageError::RuntimeError(format!("Invalid ONNX model input: {}", error))
+    })?,
+  );
+  let outputs = Value::from_array(session.allocator(), &input)
+    .and_then(|input| session.run(vec![input]))
+    .map_err(|error| {
+      MessageError::RuntimeError(format!("Unable to run the ONNX model: {}", error))
+    })?;
+  let output: OrtOwnedTensor<f32, _> = outputs[0].try_extract().map_err(|error| {
+    MessageError::RuntimeError(format!("Unexpected ONNX model output: {}", error))
+  })?;
+  let output = output.view();
+  Ok((output.shape().to_vec(), output.iter().cloned().collect()))
+}
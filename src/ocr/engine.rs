@@ -0,0 +1,76 @@
+use super::aws_text::AwsTextEngine;
+use super::azure_read::AzureReadEngine;
+use super::google_vision::GoogleVisionEngine;
+use super::onnx::OnnxEngine;
+use super::tesseract::TesseractEngine;
+use super::{FrameBuffer, OcrSettings, Recognition};
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use std::fmt::Debug;
+
+/// OCR engine, initialized once for the job then fed with each processed frame
+pub trait OcrEngine: Debug + Send + Sync {
+  /// Recognise the text of the frame, already cropped to the region of interest,
+  /// with the words, lines and blocks located in it
+  fn recognise(
+    &mut self,
+    buffer: &FrameBuffer,
+    settings: &OcrSettings,
+    page: i32,
+  ) -> Result<Recognition, MessageError>;
+}
+
+/// Available OCR engines
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OcrEngineKind {
+  Tesseract,
+  /// PaddleOCR-like detection and recognition models, run with ONNX
+  Onnx,
+  /// Google Cloud Vision text detection
+  GoogleVision,
+  /// AWS Rekognition or Textract text detection
+  Aws,
+  /// Azure Computer Vision Read API
+  AzureRead,
+}
+
+impl Default for OcrEngineKind {
+  fn default() -> Self {
+    OcrEngineKind::Tesseract
+  }
+}
+
+/// Initialize the engine selected in the settings
+pub fn create_engine(settings: &OcrSettings) -> Result<Box<dyn OcrEngine>, MessageError> {
+  match settings.engine {
+    OcrEngineKind::Tesseract => Ok(Box::new(TesseractEngine::new(settings)?)),
+    OcrEngineKind::Onnx => {
+      let parameters = settings.onnx.as_ref().ok_or_else(|| {
+        MessageError::ParameterValueError("Missing onnx parameters for the onnx engine".to_string())
+      })?;
+      Ok(Box::new(OnnxEngine::new(parameters, settings.device)?))
+    }
+    OcrEngineKind::GoogleVision => {
+      let parameters = settings.google_vision.as_ref().ok_or_else(|| {
+        MessageError::ParameterValueError(
+          "Missing google_vision parameters for the google_vision engine".to_string(),
+        )
+      })?;
+      Ok(Box::new(GoogleVisionEngine::new(parameters)?))
+    }
+    OcrEngineKind::Aws => {
+      let parameters = settings.aws.as_ref().ok_or_else(|| {
+        MessageError::ParameterValueError("Missing aws parameters for the aws engine".to_string())
+      })?;
+      Ok(Box::new(AwsTextEngine::new(parameters)?))
+    }
+    OcrEngineKind::AzureRead => {
+      let parameters = settings.azure_read.as_ref().ok_or_else(|| {
+        MessageError::ParameterValueError(
+          "Missing azure_read parameters for the azure_read engine".to_string(),
+        )
+      })?;
+      Ok(Box::new(AzureReadEngine::new(parameters)?))
+    }
+  }
+}
@@ -0,0 +1,333 @@
+use super::cloud::{encode_jpeg, send_with_retries, RateLimiter};
+use super::{FrameBuffer, OcrEngine, OcrSettings, Recognition};
+use crate::bidi::TextDirection;
+use crate::environment::parameter_or_environment;
+use crate::layout::{Block, Line, Paragraph, TextLine, Word};
+use crate::region::Rectangle;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+
+const DEFAULT_ENDPOINT: &str = "https://vision.googleapis.com";
+const SERVICE: &str = "Google Vision";
+
+/// Google Cloud Vision text detection, with the credentials of the project
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+pub struct GoogleVisionParameters {
+  /// API key of the project (default: GOOGLE_API_KEY environment variable)
+  api_key: Option<String>,
+  /// OAuth 2.0 access token, used instead of the API key (default: GOOGLE_ACCESS_TOKEN environment variable)
+  access_token: Option<String>,
+  /// Use the document text detection, for dense text, rather than the text detection (default: false)
+  document: Option<bool>,
+  /// Languages of the text as BCP-47 codes, like en or ar (default: detected by Google Vision)
+  language_hints: Option<Vec<String>>,
+  /// Quality of the JPEG frames sent to Google Vision, between 1 and 100 (default: 90)
+  jpeg_quality: Option<u8>,
+  /// Maximum number of requests per minute (default: unlimited)
+  requests_per_minute: Option<u32>,
+  /// Number of retries of a failed request (default: 3)
+  retries: Option<u32>,
+  /// Endpoint of the API (default: https://vision.googleapis.com)
+  endpoint: Option<String>,
+}
+
+#[derive(Debug)]
+enum Credentials {
+  ApiKey(String),
+  AccessToken(String),
+}
+
+/// OCR engine sending each frame to Google Cloud Vision
+#[derive(Debug)]
+pub struct GoogleVisionEngine {
+  parameters: GoogleVisionParameters,
+  credentials: Credentials,
+  rate_limiter: RateLimiter,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Vertex {
+  x: f64,
+  y: f64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct BoundingPoly {
+  vertices: Vec<Vertex>,
+}
+
+impl BoundingPoly {
+  fn rectangle(&self) -> Rectangle {
+    Rectangle::bounding_polygon(self.vertices.iter().map(|vertex| (vertex.x, vertex.y)))
+  }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct DetectedBreak {
+  #[serde(rename = "type")]
+  kind: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct TextProperty {
+  detected_break: Option<DetectedBreak>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Symbol {
+  text: String,
+  property: Option<TextProperty>,
+}
+
+impl Symbol {
+  fn detected_break(&self) -> Option<&str> {
+    self
+      .property
+      .as_ref()
+      .and_then(|property| property.detected_break.as_ref())
+      .map(|detected_break| detected_break.kind.as_str())
+  }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct VisionWord {
+  bounding_box: BoundingPoly,
+  symbols: Vec<Symbol>,
+  confidence: f32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct VisionParagraph {
+  bounding_box: BoundingPoly,
+  words: Vec<VisionWord>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct VisionBlock {
+  bounding_box: BoundingPoly,
+  paragraphs: Vec<VisionParagraph>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Page {
+  blocks: Vec<VisionBlock>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct FullTextAnnotation {
+  text: String,
+  pages: Vec<Page>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Status {
+  message: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct AnnotateImageResponse {
+  full_text_annotation: Option<FullTextAnnotation>,
+  error: Option<Status>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct BatchAnnotateImagesResponse {
+  responses: Vec<AnnotateImageResponse>,
+}
+
+/// Text line of the paragraph, closed by the line breaks detected after its last word
+fn text_line(words: Vec<Word>) -> TextLine {
+  let text = words
+    .iter()
+    .map(|word| word.text.as_str())
+    .collect::<Vec<&str>>()
+    .join(" ");
+  let bounding_box = words
+    .iter()
+    .skip(1)
+    .fold(words[0].bounding_box, |bounding_box, word| {
+      bounding_box.union(&word.bounding_box)
+    });
+  let confidence = words.iter().map(|word| word.confidence).sum::<f32>() / words.len() as f32;
+
+  TextLine {
+    line: Line {
+      direction: TextDirection::of(&text),
+      text,
+      bounding_box,
+      baseline: None,
+      confidence,
+    },
+    words,
+  }
+}
+
+impl GoogleVisionEngine {
+  pub fn new(parameters: &GoogleVisionParameters) -> Result<Self, MessageError> {
+    let credentials = match (
+      parameter_or_environment(&parameters.access_token, &["GOOGLE_ACCESS_TOKEN"]),
+      parameter_or_environment(&parameters.api_key, &["GOOGLE_API_KEY"]),
+    ) {
+      (Some(access_token), _) => Credentials::AccessToken(access_token),
+      (None, Some(api_key)) => Credentials::ApiKey(api_key),
+      (None, None) => {
+        return Err(MessageError::ParameterValueError(
+          "Missing Google Vision API key or access token".to_string(),
+        ))
+      }
+    };
+
+    Ok(GoogleVisionEngine {
+      parameters: parameters.clone(),
+      credentials,
+      rate_limiter: RateLimiter::new(parameters.requests_per_minute),
+    })
+  }
+
+  fn annotate(&mut self, jpeg: &[u8]) -> Result<AnnotateImageResponse, MessageError> {
+    let feature = if self.parameters.document.unwrap_or(false) {
+      "DOCUMENT_TEXT_DETECTION"
+    } else {
+      "TEXT_DETECTION"
+    };
+    let mut request = json!({
+      "image": { "content": base64::encode(jpeg) },
+      "features": [{ "type": feature }],
+    });
+    if let Some(language_hints) = &self.parameters.language_hints {
+      request["imageContext"] = json!({ "languageHints": language_hints });
+    }
+    let body = json!({ "requests": [request] });
+
+    let url = format!(
+      "{}/v1/images:annotate",
+      self
+        .parameters
+        .endpoint
+        .as_deref()
+        .unwrap_or(DEFAULT_ENDPOINT)
+        .trim_end_matches('/')
+    );
+
+    self.rate_limiter.wait();
+    let response = send_with_retries(SERVICE, self.parameters.retries.unwrap_or(3), || {
+      let mut request = ureq::post(&url);
+      match &self.credentials {
+        Credentials::ApiKey(api_key) => request.query("key", api_key),
+        Credentials::AccessToken(access_token) => {
+          request.set("Authorization", &format!("Bearer {}", access_token))
+        }
+      };
+      request.send_json(body.clone())
+    })?;
+
+    let response = response
+      .into_json_deserialize::<BatchAnnotateImagesResponse>()
+      .map_err(|error| {
+        MessageError::RuntimeError(format!("Invalid {} response: {}", SERVICE, error))
+      })?;
+    let response = response.responses.into_iter().next().unwrap_or_default();
+    if let Some(error) = &response.error {
+      return Err(MessageError::RuntimeError(format!(
+        "{} is unable to annotate the frame: {}",
+        SERVICE, error.message
+      )));
+    }
+    Ok(response)
+  }
+}
+
+impl OcrEngine for GoogleVisionEngine {
+  fn recognise(
+    &mut self,
+    buffer: &FrameBuffer,
+    settings: &OcrSettings,
+    _page: i32,
+  ) -> Result<Recognition, MessageError> {
+    let jpeg = encode_jpeg(buffer, self.parameters.jpeg_quality.unwrap_or(90))?;
+    let annotation = match self.annotate(&jpeg)?.full_text_annotation {
+      Some(annotation) => annotation,
+      None => return Ok(Recognition::default()),
+    };
+
+    let mut words = vec![];
+    let mut lines = vec![];
+    let mut blocks = vec![];
+    let vision_blocks = annotation
+      .pages
+      .into_iter()
+      .flat_map(|page| page.blocks.into_iter());
+    for (block_index, vision_block) in vision_blocks.enumerate() {
+      let mut paragraphs = vec![];
+      for (paragraph_index, vision_paragraph) in vision_block.paragraphs.into_iter().enumerate() {
+        let mut text_lines = vec![];
+        let mut line_words = vec![];
+        for vision_word in vision_paragraph.words {
+          let ends_line = vision_word
+            .symbols
+            .last()
+            .and_then(Symbol::detected_break)
+            .map(|kind| kind == "LINE_BREAK" || kind == "EOL_SURE_SPACE")
+            .unwrap_or(false);
+
+          line_words.push(Word {
+            text: vision_word
+              .symbols
+              .iter()
+              .map(|symbol| symbol.text.as_str())
+              .collect(),
+            bounding_box: vision_word.bounding_box.rectangle(),
+            confidence: vision_word.confidence * 100.0,
+            block: block_index as u32 + 1,
+            paragraph: paragraph_index as u32 + 1,
+            line: text_lines.len() as u32 + 1,
+          });
+          if ends_line {
+            text_lines.push(text_line(line_words));
+            line_words = vec![];
+          }
+        }
+        if !line_words.is_empty() {
+          text_lines.push(text_line(line_words));
+        }
+
+        for text_line in &text_lines {
+          words.extend(text_line.words.iter().cloned());
+          lines.push(text_line.line.clone());
+        }
+        paragraphs.push(Paragraph {
+          bounding_box: vision_paragraph.bounding_box.rectangle(),
+          lines: text_lines,
+        });
+      }
+      blocks.push(Block {
+        bounding_box: vision_block.bounding_box.rectangle(),
+        paragraphs,
+      });
+    }
+
+    if !settings.layout {
+      blocks.clear();
+    }
+
+    Ok(Recognition {
+      text: annotation.text,
+      hocr: None,
+      words,
+      lines,
+      blocks,
+    })
+  }
+}
@@ -0,0 +1,19 @@
+use mcai_worker_sdk::JsonSchema;
+
+/// Language of the text, or list of languages recognised in one pass
+#[derive(Clone, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Language {
+  Single(String),
+  List(Vec<String>),
+}
+
+impl Language {
+  /// Languages in Tesseract syntax, like `eng+fra+deu`
+  pub fn to_tesseract(&self) -> String {
+    match self {
+      Language::Single(language) => language.clone(),
+      Language::List(languages) => languages.join("+"),
+    }
+  }
+}
@@ -0,0 +1,103 @@
+use super::preprocessing::luma;
+use super::FrameBuffer;
+use crate::region::Rectangle;
+use mcai_worker_sdk::RegionOfInterest;
+
+/// Processed frames on which the black bars are detected
+const LETTERBOX_FRAMES: u32 = 25;
+/// Mean luminance of the rows and columns of the black bars, like the cropdetect limit of FFmpeg
+const BLACK_LIMIT: u64 = 24;
+
+/// Removal of the black bars of the letterboxed and pillarboxed frames, the region of interest
+/// being set against the active picture as displayed, rotated clockwise by `rotation` degrees
+#[derive(Debug)]
+pub struct Letterbox {
+  region_of_interest: Option<RegionOfInterest>,
+  rotation: u32,
+  /// Active picture of the frames detected so far, in the decoded frame
+  active_area: Option<Rectangle>,
+  detected_frames: u32,
+}
+
+impl Letterbox {
+  pub fn new(region_of_interest: Option<RegionOfInterest>, rotation: u32) -> Self {
+    Letterbox {
+      region_of_interest,
+      rotation,
+      active_area: None,
+      detected_frames: 0,
+    }
+  }
+
+  /// Grow the active picture with the one of the frame, on the first frames which are not black
+  pub fn detect(&mut self, buffer: &FrameBuffer) {
+    if self.detected_frames >= LETTERBOX_FRAMES {
+      return;
+    }
+    let area = match active_area(buffer) {
+      Some(area) => area,
+      None => return,
+    };
+    self.detected_frames += 1;
+    self.active_area = Some(match &self.active_area {
+      Some(active_area) => active_area.union(&area),
+      None => area,
+    });
+  }
+
+  /// Area of the decoded frame to recognise, the region of interest in the active picture
+  pub fn area(&self, buffer: &FrameBuffer) -> Rectangle {
+    let active_area = self.active_area.unwrap_or(Rectangle {
+      left: 0,
+      top: 0,
+      width: buffer.width as u32,
+      height: buffer.height as u32,
+    });
+    match &self.region_of_interest {
+      Some(region_of_interest) => {
+        let area = Rectangle::from_displayed_region_of_interest(
+          region_of_interest,
+          self.rotation,
+          active_area.width,
+          active_area.height,
+        );
+        Rectangle {
+          left: active_area.left + area.left,
+          top: active_area.top + area.top,
+          ..area
+        }
+      }
+      None => active_area,
+    }
+  }
+}
+
+/// Rows and columns of the frame brighter than the black bars, None when the frame is black
+fn active_area(buffer: &FrameBuffer) -> Option<Rectangle> {
+  let (width, height) = (buffer.width as usize, buffer.height as usize);
+  if width == 0 || height == 0 {
+    return None;
+  }
+  let luma = luma(buffer);
+  let row_active = |y: usize| {
+    luma[y * width..(y + 1) * width]
+      .iter()
+      .map(|value| *value as u64)
+      .sum::<u64>()
+      > BLACK_LIMIT * width as u64
+  };
+  let column_active = |x: usize| {
+    (0..height).map(|y| luma[y * width + x] as u64).sum::<u64>() > BLACK_LIMIT * height as u64
+  };
+
+  let top = (0..height).find(|y| row_active(*y))?;
+  let bottom = (0..height).rev().find(|y| row_active(*y))?;
+  let left = (0..width).find(|x| column_active(*x))?;
+  let right = (0..width).rev().find(|x| column_active(*x))?;
+  Some(Rectangle {
+    left: left as u32,
+    top: top as u32,
+    width: (right - left + 1) as u32,
+    height: (bottom - top + 1) as u32,
+  })
+}
@@ -0,0 +1,318 @@
+mod alpha;
+mod averaging;
+mod aws_text;
+mod azure_read;
+mod cloud;
+mod detection;
+mod device;
+mod engine;
+mod google_vision;
+mod language;
+mod letterbox;
+mod mode;
+mod model_cache;
+mod onnx;
+mod osd;
+mod preprocessing;
+mod regions;
+mod retry;
+mod rotation;
+mod snapshot;
+mod spell_correction;
+mod tess_base_api;
+mod tesseract;
+mod text_filter;
+mod tone_mapping;
+mod user_list;
+
+use crate::bidi;
+use crate::layout::{mean_confidence, words_text, Block, Line, Word};
+pub use alpha::AlphaCompositing;
+pub use averaging::{TemporalAverage, TemporalAverageParameters};
+pub use aws_text::AwsTextParameters;
+pub use azure_read::AzureReadParameters;
+pub use detection::TextDetectionParameters;
+pub use device::Device;
+pub use engine::{create_engine, OcrEngine, OcrEngineKind};
+pub use google_vision::GoogleVisionParameters;
+pub use language::Language;
+pub use letterbox::Letterbox;
+use mcai_worker_sdk::MessageError;
+pub use mode::{OcrEngineMode, PageSegmentationMode};
+pub use model_cache::ModelDownloadParameters;
+pub use onnx::OnnxParameters;
+pub use osd::{apply_detected_script, AutoLanguage};
+pub use preprocessing::{
+  levels, luma, BinarizationParameters, Channel, DenoiseParameters, Invert, MorphologyParameters,
+  Preprocessing, PreprocessingSettings, UnsharpParameters, UpscaleParameters,
+};
+pub use regions::{recognise_regions, RegionParameters, RegionRecognizer};
+pub use retry::{upscaled, LowConfidenceRetry, LowConfidenceRetryParameters};
+pub use rotation::{display_rotation, OwnedFrame};
+pub use spell_correction::{SpellChecker, SpellCorrectionParameters};
+use std::collections::HashSet;
+use tesseract::check_languages;
+pub use text_filter::TextFilter;
+pub use tone_mapping::{ToneMapping, Transfer};
+pub use user_list::UserList;
+
+/// Packed pixels of a decoded frame, as expected by Tesseract
+#[derive(Clone, Copy)]
+pub struct FrameBuffer<'a> {
+  pub data: &'a [u8],
+  pub width: i32,
+  pub height: i32,
+  pub bytes_per_pixel: i32,
+  pub bytes_per_line: i32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct OcrSettings {
+  pub engine: OcrEngineKind,
+  /// Text detection locating the areas given to Tesseract
+  pub text_detection: Option<TextDetectionParameters>,
+  /// Models of the onnx engine
+  pub onnx: Option<OnnxParameters>,
+  /// Credentials and options of the google_vision engine
+  pub google_vision: Option<GoogleVisionParameters>,
+  /// Credentials and options of the aws engine
+  pub aws: Option<AwsTextParameters>,
+  /// Credentials and options of the azure_read engine
+  pub azure_read: Option<AzureReadParameters>,
+  /// Device running the ONNX models
+  pub device: Device,
+  /// Directory of the traineddata files, Tesseract's own location when not set
+  pub tessdata_path: Option<String>,
+  pub language: String,
+  pub hocr: bool,
+  pub layout: bool,
+  pub min_confidence: Option<f32>,
+  pub min_text_length: Option<usize>,
+  /// Patterns selecting the recognised lines to keep
+  pub text_filter: Option<TextFilter>,
+  pub page_segmentation_mode: Option<PageSegmentationMode>,
+  pub engine_mode: OcrEngineMode,
+  /// Tesseract variables set at initialization, like the user words file
+  pub variables: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+pub struct Recognition {
+  pub text: String,
+  pub hocr: Option<String>,
+  pub words: Vec<Word>,
+  pub lines: Vec<Line>,
+  pub blocks: Vec<Block>,
+}
+
+impl OcrSettings {
+  /// Recognise a single line of the given characters, keeping the page segmentation mode when set
+  pub fn restrict_to_line(&mut self, characters: &str) {
+    self
+      .page_segmentation_mode
+      .get_or_insert(PageSegmentationMode::SingleLine);
+    self.variables.push((
+      "tessedit_char_whitelist".to_string(),
+      characters.to_string(),
+    ));
+  }
+
+  /// Load a user list with the `<name>_file` Tesseract variable, like user_words or user_patterns
+  pub fn add_user_list(&mut self, name: &str, list: &UserList) -> Result<(), MessageError> {
+    let path = list.to_file(&name.replace('_', "-"))?;
+    self.variables.push((format!("{}_file", name), path));
+    Ok(())
+  }
+}
+
+impl Recognition {
+  /// Append the recognition of another area of the frame, numbering its blocks after these ones
+  pub fn append(&mut self, mut other: Recognition) {
+    let block_offset = self.words.iter().map(|word| word.block).max().unwrap_or(0);
+    for word in &mut other.words {
+      word.block += block_offset;
+    }
+    for block in &mut other.blocks {
+      for paragraph in &mut block.paragraphs {
+        for text_line in &mut paragraph.lines {
+          for word in &mut text_line.words {
+            word.block += block_offset;
+          }
+        }
+      }
+    }
+
+    self.text.push_str(&other.text);
+    if let Some(other_hocr) = other.hocr {
+      self
+        .hocr
+        .get_or_insert_with(String::new)
+        .push_str(&other_hocr);
+    }
+    self.words.append(&mut other.words);
+    self.lines.append(&mut other.lines);
+    self.blocks.append(&mut other.blocks);
+  }
+
+  /// Remove the directional formatting characters of the texts, keeping them in logical order
+  pub fn normalize_bidi(&mut self) {
+    self.text = bidi::normalize(&self.text);
+    for word in &mut self.words {
+      word.text = bidi::normalize(&word.text);
+    }
+    for line in &mut self.lines {
+      line.text = bidi::normalize(&line.text);
+    }
+    for block in &mut self.blocks {
+      for paragraph in &mut block.paragraphs {
+        for text_line in &mut paragraph.lines {
+          text_line.line.text = bidi::normalize(&text_line.line.text);
+          for word in &mut text_line.words {
+            word.text = bidi::normalize(&word.text);
+          }
+        }
+      }
+    }
+  }
+
+  /// Confidence of the most confident line, or word when the engine returns no lines
+  pub fn best_confidence(&self) -> Option<f32> {
+    let confidences: Vec<f32> = if self.lines.is_empty() {
+      self.words.iter().map(|word| word.confidence).collect()
+    } else {
+      self.lines.iter().map(|line| line.confidence).collect()
+    };
+    confidences.into_iter().reduce(f32::max)
+  }
+
+  /// Drop the words and lines recognised with a confidence below the threshold,
+  /// and all the text of the frame when its mean confidence is below it.
+  pub fn retain_confident(&mut self, min_confidence: f32) {
+    if let Some(confidence) = mean_confidence(&self.words) {
+      if confidence < min_confidence {
+        self.text.clear();
+        self.words.clear();
+        self.lines.clear();
+        self.blocks.clear();
+        return;
+      }
+    }
+
+    self.retain(
+      |word| word.confidence >= min_confidence,
+      |line| line.confidence >= min_confidence,
+    );
+  }
+
+  /// Drop the words and lines shorter than the minimum length, in characters after trimming
+  pub fn retain_long_enough(&mut self, min_text_length: usize) {
+    let is_long_enough = |text: &str| text.trim().chars().count() >= min_text_length;
+
+    self.retain(
+      |word| is_long_enough(&word.text),
+      |line| is_long_enough(&line.text),
+    );
+  }
+
+  /// Drop the lines rejected by the filter, with their words
+  pub fn retain_matching(&mut self, filter: &TextFilter) {
+    let line_key = |word: &Word| (word.block, word.paragraph, word.line);
+
+    // text of the lines of the words, as the lines are not numbered
+    let mut line_texts: Vec<((u32, u32, u32), String)> = vec![];
+    for word in &self.words {
+      match line_texts.last_mut() {
+        Some((key, text)) if *key == line_key(word) => {
+          text.push(' ');
+          text.push_str(&word.text);
+        }
+        _ => line_texts.push((line_key(word), word.text.clone())),
+      }
+    }
+    let kept_lines: HashSet<(u32, u32, u32)> = line_texts
+      .into_iter()
+      .filter(|(_, text)| filter.keeps(text))
+      .map(|(key, _)| key)
+      .collect();
+
+    self.retain(
+      |word| kept_lines.contains(&line_key(word)),
+      |line| filter.keeps(&line.text),
+    );
+  }
+
+  /// Keep the matching words and lines, then rebuild the text from the kept words
+  fn retain(&mut self, keep_word: impl Fn(&Word) -> bool, keep_line: impl Fn(&Line) -> bool) {
+    self.words.retain(|word| keep_word(word));
+    self.lines.retain(|line| keep_line(line));
+    for block in &mut self.blocks {
+      for paragraph in &mut block.paragraphs {
+        for text_line in &mut paragraph.lines {
+          text_line.words.retain(|word| keep_word(word));
+        }
+        paragraph
+          .lines
+          .retain(|text_line| !text_line.words.is_empty());
+      }
+      block
+        .paragraphs
+        .retain(|paragraph| !paragraph.lines.is_empty());
+    }
+    self.blocks.retain(|block| !block.paragraphs.is_empty());
+
+    self.text = words_text(&self.words);
+  }
+}
+
+/// Run the OCR on a frame with the engine of the job, `page` numbers the hOCR page of the frame
+pub fn recognise(
+  engine: &mut dyn OcrEngine,
+  buffer: &FrameBuffer,
+  settings: &OcrSettings,
+  page: i32,
+) -> Result<Recognition, MessageError> {
+  let mut recognition = engine.recognise(buffer, settings, page)?;
+
+  recognition.normalize_bidi();
+  if let Some(min_confidence) = settings.min_confidence {
+    recognition.retain_confident(min_confidence);
+  }
+  if let Some(min_text_length) = settings.min_text_length {
+    recognition.retain_long_enough(min_text_length);
+  }
+  if let Some(text_filter) = &settings.text_filter {
+    recognition.retain_matching(text_filter);
+  }
+  Ok(recognition)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn user_lists_set_their_tesseract_variables() {
+    let mut settings = OcrSettings::default();
+    settings
+      .add_user_list("user_words", &UserList::File("/tmp/words.txt".to_string()))
+      .unwrap();
+    settings
+      .add_user_list(
+        "user_patterns",
+        &UserList::Items(vec![r"\d\d:\d\d:\d\d".to_string()]),
+      )
+      .unwrap();
+
+    assert_eq!(
+      settings.variables[0],
+      ("user_words_file".to_string(), "/tmp/words.txt".to_string())
+    );
+    let (name, path) = &settings.variables[1];
+    assert_eq!(name, "user_patterns_file");
+    assert!(path.ends_with("-user-patterns.txt"));
+    assert_eq!(
+      std::fs::read_to_string(path).unwrap(),
+      "\\d\\d:\\d\\d:\\d\\d\n"
+    );
+  }
+}
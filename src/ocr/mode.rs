@@ -0,0 +1,88 @@
+use mcai_worker_sdk::JsonSchema;
+use tesseract_sys::*;
+
+/// Tesseract page segmentation mode, describing the layout of the text in the frame
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageSegmentationMode {
+  /// Orientation and script detection only (PSM 0)
+  OsdOnly,
+  /// Automatic page segmentation with orientation and script detection (PSM 1)
+  AutoOsd,
+  /// Automatic page segmentation, without OSD nor OCR (PSM 2)
+  AutoOnly,
+  /// Fully automatic page segmentation, without OSD (PSM 3)
+  Auto,
+  /// Single column of text of variable sizes (PSM 4)
+  SingleColumn,
+  /// Single uniform block of vertically aligned text (PSM 5)
+  SingleBlockVertText,
+  /// Single uniform block of text (PSM 6)
+  SingleBlock,
+  /// Single text line, like a lower third (PSM 7)
+  SingleLine,
+  /// Single word (PSM 8)
+  SingleWord,
+  /// Single word in a circle (PSM 9)
+  CircleWord,
+  /// Single character (PSM 10)
+  SingleChar,
+  /// As much text as possible, in no particular order (PSM 11)
+  SparseText,
+  /// Sparse text with orientation and script detection (PSM 12)
+  SparseTextOsd,
+  /// Single text line, bypassing the Tesseract specific hacks (PSM 13)
+  RawLine,
+}
+
+impl PageSegmentationMode {
+  pub fn to_tesseract(self) -> TessPageSegMode {
+    match self {
+      PageSegmentationMode::OsdOnly => TessPageSegMode_PSM_OSD_ONLY,
+      PageSegmentationMode::AutoOsd => TessPageSegMode_PSM_AUTO_OSD,
+      PageSegmentationMode::AutoOnly => TessPageSegMode_PSM_AUTO_ONLY,
+      PageSegmentationMode::Auto => TessPageSegMode_PSM_AUTO,
+      PageSegmentationMode::SingleColumn => TessPageSegMode_PSM_SINGLE_COLUMN,
+      PageSegmentationMode::SingleBlockVertText => TessPageSegMode_PSM_SINGLE_BLOCK_VERT_TEXT,
+      PageSegmentationMode::SingleBlock => TessPageSegMode_PSM_SINGLE_BLOCK,
+      PageSegmentationMode::SingleLine => TessPageSegMode_PSM_SINGLE_LINE,
+      PageSegmentationMode::SingleWord => TessPageSegMode_PSM_SINGLE_WORD,
+      PageSegmentationMode::CircleWord => TessPageSegMode_PSM_CIRCLE_WORD,
+      PageSegmentationMode::SingleChar => TessPageSegMode_PSM_SINGLE_CHAR,
+      PageSegmentationMode::SparseText => TessPageSegMode_PSM_SPARSE_TEXT,
+      PageSegmentationMode::SparseTextOsd => TessPageSegMode_PSM_SPARSE_TEXT_OSD,
+      PageSegmentationMode::RawLine => TessPageSegMode_PSM_RAW_LINE,
+    }
+  }
+}
+
+/// Tesseract recognition engine
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OcrEngineMode {
+  /// Legacy engine, faster on clean text like numeric overlays
+  Legacy,
+  /// Neural network LSTM engine
+  Lstm,
+  /// Legacy and LSTM engines combined
+  Combined,
+  /// Engine available in the traineddata files
+  Default,
+}
+
+impl Default for OcrEngineMode {
+  fn default() -> Self {
+    OcrEngineMode::Default
+  }
+}
+
+impl OcrEngineMode {
+  pub fn to_tesseract(self) -> TessOcrEngineMode {
+    match self {
+      OcrEngineMode::Legacy => TessOcrEngineMode_OEM_TESSERACT_ONLY,
+      OcrEngineMode::Lstm => TessOcrEngineMode_OEM_LSTM_ONLY,
+      OcrEngineMode::Combined => TessOcrEngineMode_OEM_TESSERACT_LSTM_COMBINED,
+      OcrEngineMode::Default => TessOcrEngineMode_OEM_DEFAULT,
+    }
+  }
+}
@@ -0,0 +1,215 @@
+use mcai_worker_sdk::{info, warn, JsonSchema, MessageError};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_MIRROR: &str = "https://github.com/tesseract-ocr";
+
+/// Variant of the Tesseract models, trading accuracy for speed
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelVariant {
+  Standard,
+  Fast,
+  Best,
+}
+
+impl Default for ModelVariant {
+  fn default() -> Self {
+    ModelVariant::Standard
+  }
+}
+
+impl ModelVariant {
+  fn repository(self) -> &'static str {
+    match self {
+      ModelVariant::Standard => "tessdata",
+      ModelVariant::Fast => "tessdata_fast",
+      ModelVariant::Best => "tessdata_best",
+    }
+  }
+}
+
+/// Download of the missing traineddata files into a local cache
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelDownloadParameters {
+  /// Base URL of the model repositories, laid out as
+  /// `<mirror>/<tessdata|tessdata_fast|tessdata_best>/raw/main/<language>.traineddata`
+  /// (default: https://github.com/tesseract-ocr)
+  mirror: Option<String>,
+  /// Variant of the models: standard, fast or best (default: standard)
+  variant: Option<ModelVariant>,
+  /// Directory of the cached models, used as the tessdata path
+  /// (default: tessdata_path, or the tessdata directory of the temporary directory)
+  cache_directory: Option<String>,
+  /// Expected SHA-256 of the traineddata files, by language.
+  /// Otherwise the `<file>.sha256` published next to the file on the mirror is used.
+  checksums: Option<HashMap<String, String>>,
+  /// Keep the models without a checksum in checksums or on the mirror, unverified (default: false)
+  allow_unverified: Option<bool>,
+}
+
+impl ModelDownloadParameters {
+  pub fn cache_directory(&self, tessdata_path: Option<&str>) -> PathBuf {
+    self
+      .cache_directory
+      .as_deref()
+      .or(tessdata_path)
+      .map(PathBuf::from)
+      .unwrap_or_else(|| env::temp_dir().join("tessdata"))
+  }
+
+  fn url(&self, language: &str) -> String {
+    format!(
+      "{}/{}/raw/main/{}.traineddata",
+      self
+        .mirror
+        .as_deref()
+        .unwrap_or(DEFAULT_MIRROR)
+        .trim_end_matches('/'),
+      self.variant.unwrap_or_default().repository(),
+      language
+    )
+  }
+
+  /// Download the languages missing from the cache directory, keeping the cached ones
+  pub fn fetch(&self, cache_directory: &Path, languages: &[&str]) -> Result<(), MessageError> {
+    for language in languages {
+      let path = cache_directory.join(format!("{}.traineddata", language));
+      if path.is_file() {
+        continue;
+      }
+      self.download(language, &path)?;
+    }
+    Ok(())
+  }
+
+  fn download(&self, language: &str, path: &Path) -> Result<(), MessageError> {
+    let url = self.url(language);
+    let expected_checksum = self.expected_checksum(language, &url)?;
+    if expected_checksum.is_none() && !self.allow_unverified.unwrap_or(false) {
+      return Err(MessageError::ParameterValueError(format!(
+        "No checksum to verify the {} model from {}: set it in checksums, or set allow_unverified",
+        language, url
+      )));
+    }
+    info!("Download the {} model from {}", language, url);
+
+    if let Some(directory) = path.parent() {
+      fs::create_dir_all(directory).map_err(|error| {
+        MessageError::RuntimeError(format!(
+          "Unable to create {}: {}",
+          directory.display(),
+          error
+        ))
+      })?;
+    }
+
+    let response = ureq::get(&url).call();
+    if let Some(error) = response.synthetic_error() {
+      return Err(MessageError::RuntimeError(format!(
+        "Unable to download {}: {}",
+        url, error
+      )));
+    }
+    if response.error() {
+      return Err(MessageError::ParameterValueError(format!(
+        "Unable to download the {} model from {}: {}",
+        language,
+        url,
+        response.status_line()
+      )));
+    }
+
+    // Written next to the cached file then renamed, so that concurrent workers never load a partial model
+    let partial_path = path.with_extension(format!("traineddata.{}.part", std::process::id()));
+    let write_error = |error: std::io::Error| {
+      MessageError::RuntimeError(format!(
+        "Unable to write {}: {}",
+        partial_path.display(),
+        error
+      ))
+    };
+    let mut file = File::create(&partial_path).map_err(write_error)?;
+    let mut reader = response.into_reader();
+    let mut hasher = Sha256::new();
+    let mut chunk = [0u8; 64 * 1024];
+    loop {
+      let size = reader.read(&mut chunk).map_err(|error| {
+        MessageError::RuntimeError(format!("Unable to download {}: {}", url, error))
+      })?;
+      if size == 0 {
+        break;
+      }
+      hasher.update(&chunk[..size]);
+      file.write_all(&chunk[..size]).map_err(write_error)?;
+    }
+    file.flush().map_err(write_error)?;
+
+    let checksum: String = hasher
+      .finalize()
+      .iter()
+      .map(|byte| format!("{:02x}", byte))
+      .collect();
+    match expected_checksum {
+      Some(expected) if !expected.eq_ignore_ascii_case(&checksum) => {
+        let _ = fs::remove_file(&partial_path);
+        return Err(MessageError::RuntimeError(format!(
+          "Invalid checksum of the {} model: expected {}, got {}",
+          language, expected, checksum
+        )));
+      }
+      Some(_) => {}
+      None => warn!(
+        "No checksum to verify the {} model, downloaded with SHA-256 {}",
+        language, checksum
+      ),
+    }
+
+    fs::rename(&partial_path, path).map_err(|error| {
+      MessageError::RuntimeError(format!("Unable to write {}: {}", path.display(), error))
+    })
+  }
+
+  /// Checksum of the checksums parameter, or of the `<file>.sha256` of the mirror when published
+  fn expected_checksum(&self, language: &str, url: &str) -> Result<Option<String>, MessageError> {
+    if let Some(checksum) = self
+      .checksums
+      .as_ref()
+      .and_then(|checksums| checksums.get(language))
+    {
+      return Ok(Some(checksum.trim().to_string()));
+    }
+
+    let checksum_url = format!("{}.sha256", url);
+    let response = ureq::get(&checksum_url).call();
+    if let Some(error) = response.synthetic_error() {
+      return Err(MessageError::RuntimeError(format!(
+        "Unable to download {}: {}",
+        checksum_url, error
+      )));
+    }
+    if response.status() == 404 {
+      return Ok(None);
+    }
+    if response.error() {
+      return Err(MessageError::RuntimeError(format!(
+        "Unable to download {}: {}",
+        checksum_url,
+        response.status_line()
+      )));
+    }
+
+    let content = response.into_string().map_err(|error| {
+      MessageError::RuntimeError(format!("Unable to download {}: {}", checksum_url, error))
+    })?;
+    content
+      .split_whitespace()
+      .next()
+      .map(|checksum| Some(checksum.to_string()))
+      .ok_or_else(|| MessageError::RuntimeError(format!("Empty checksum in {}", checksum_url)))
+  }
+}
@@ -0,0 +1,312 @@
+#[cfg(feature = "onnx")]
+use super::detection::{
+  area_tensor, load_model, run_model, Plan, TextDetectionParameters, TextDetector, TextRegion,
+};
+use super::{Device, FrameBuffer, OcrEngine, OcrSettings, Recognition};
+#[cfg(feature = "onnx")]
+use crate::bidi::TextDirection;
+#[cfg(feature = "onnx")]
+use crate::layout::{Block, Line, Paragraph, TextLine, Word};
+#[cfg(feature = "onnx")]
+use crate::region::Rectangle;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+#[cfg(feature = "onnx")]
+use std::fs;
+
+#[cfg(feature = "onnx")]
+const DEFAULT_RECOGNITION_HEIGHT: u32 = 48;
+#[cfg(feature = "onnx")]
+const DEFAULT_RECOGNITION_WIDTH: u32 = 320;
+
+/// PaddleOCR-like detection and recognition models, exported to ONNX
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+#[cfg_attr(not(feature = "onnx"), allow(dead_code))]
+pub struct OnnxParameters {
+  /// Path of the text detection model, a DB model predicting a text probability map
+  detection_model: String,
+  /// Path of the text recognition model, a CTC model predicting the characters of a text line
+  recognition_model: String,
+  /// Path of the characters dictionary of the recognition model, one character per line
+  dictionary: String,
+  /// Longest side of the frames given to the detection model, in pixels (default: 960)
+  detection_size: Option<u32>,
+  /// Probability of the text pixels on the detection map, between 0 and 1 (default: 0.3)
+  detection_threshold: Option<f32>,
+  /// Minimum mean probability of a detected text region, between 0 and 1 (default: 0.6)
+  box_threshold: Option<f32>,
+  /// Height of the text lines given to the recognition model (default: 48)
+  recognition_height: Option<u32>,
+  /// Width of the text lines given to the recognition model, padding the shorter ones (default: 320)
+  recognition_width: Option<u32>,
+}
+
+/// OCR engine running the detection then the recognition models with tract
+pub struct OnnxEngine {
+  #[cfg(feature = "onnx")]
+  parameters: OnnxParameters,
+  #[cfg(feature = "onnx")]
+  detector: TextDetector,
+  #[cfg(feature = "onnx")]
+  recognition: Plan,
+  #[cfg(feature = "onnx")]
+  dictionary: Vec<String>,
+}
+
+impl std::fmt::Debug for OnnxEngine {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    f.debug_struct("OnnxEngine").finish()
+  }
+}
+
+#[cfg(feature = "onnx")]
+impl OnnxEngine {
+  pub fn new(parameters: &OnnxParameters, device: Device) -> Result<Self, MessageError> {
+    let dictionary = fs::read_to_string(&parameters.dictionary)
+      .map_err(|error| {
+        MessageError::ParameterValueError(format!(
+          "Unable to read the dictionary {}: {}",
+          parameters.dictionary, error
+        ))
+      })?
+      .lines()
+      .map(|character| character.trim_end_matches('\r').to_string())
+      .collect();
+
+    let recognition = load_model(
+      &parameters.recognition_model,
+      [
+        1,
+        3,
+        parameters
+          .recognition_height
+          .unwrap_or(DEFAULT_RECOGNITION_HEIGHT) as usize,
+        parameters
+          .recognition_width
+          .unwrap_or(DEFAULT_RECOGNITION_WIDTH) as usize,
+      ],
+      device,
+    )?;
+
+    let detector = TextDetector::new(
+      &TextDetectionParameters {
+        model: parameters.detection_model.clone(),
+        size: parameters.detection_size,
+        threshold: parameters.detection_threshold,
+        box_threshold: parameters.box_threshold,
+      },
+      device,
+    )?;
+
+    Ok(OnnxEngine {
+      parameters: parameters.clone(),
+      detector,
+      recognition,
+      dictionary,
+    })
+  }
+
+  /// Read the text line of a region, locating its words from the CTC time steps
+  fn read_line(
+    &self,
+    buffer: &FrameBuffer,
+    region: &TextRegion,
+    line_number: u32,
+  ) -> Result<Option<TextLine>, MessageError> {
+    let area = &region.area;
+    let recognition_height = self
+      .parameters
+      .recognition_height
+      .unwrap_or(DEFAULT_RECOGNITION_HEIGHT) as usize;
+    let recognition_width = self
+      .parameters
+      .recognition_width
+      .unwrap_or(DEFAULT_RECOGNITION_WIDTH) as usize;
+    let resized_width =
+      ((area.width as f32 * recognition_height as f32 / area.height as f32).ceil() as usize)
+        .max(1)
+        .min(recognition_width);
+
+    let input = area_tensor(
+      buffer,
+      area,
+      resized_width,
+      (recognition_height, recognition_width),
+      |_, value| (value - 0.5) / 0.5,
+    );
+    let (shape, probabilities) = run_model(&self.recognition, input)?;
+    let (steps, classes) = match shape.as_slice() {
+      [.., steps, classes] if *steps > 0 && *classes > 0 => (*steps, *classes),
+      _ => return Ok(None),
+    };
+
+    // greedy CTC decoding, the class 0 being the blank and the one after the dictionary the space
+    let mut characters = vec![];
+    let mut previous = 0;
+    for step in 0..steps {
+      let scores = &probabilities[step * classes..(step + 1) * classes];
+      let (class, probability) =
+        scores
+          .iter()
+          .cloned()
+          .enumerate()
+          .fold((0, f32::MIN), |best, (class, probability)| {
+            if probability > best.1 {
+              (class, probability)
+            } else {
+              best
+            }
+          });
+      if class != 0 && class != previous {
+        let character = self
+          .dictionary
+          .get(class - 1)
+          .map(String::as_str)
+          .unwrap_or(" ");
+        characters.push((step, character, probability));
+      }
+      previous = class;
+    }
+    if characters.is_empty() {
+      return Ok(None);
+    }
+
+    let step_x = |step: usize| {
+      let x = step * recognition_width / steps * area.width as usize / resized_width;
+      area.left + (x as u32).min(area.width)
+    };
+    let confidence = characters
+      .iter()
+      .map(|(_, _, probability)| probability)
+      .sum::<f32>()
+      / characters.len() as f32
+      * 100.0;
+
+    let mut words = vec![];
+    for word_characters in characters.split(|(_, character, _)| character.trim().is_empty()) {
+      if let (Some(first), Some(last)) = (word_characters.first(), word_characters.last()) {
+        let (left, right) = (step_x(first.0), step_x(last.0 + 1));
+        words.push(Word {
+          text: word_characters
+            .iter()
+            .map(|(_, character, _)| *character)
+            .collect(),
+          bounding_box: Rectangle {
+            left,
+            top: area.top,
+            width: (right - left).max(1),
+            height: area.height,
+          },
+          confidence: word_characters
+            .iter()
+            .map(|(_, _, probability)| probability)
+            .sum::<f32>()
+            / word_characters.len() as f32
+            * 100.0,
+          block: 1,
+          paragraph: 1,
+          line: line_number,
+        });
+      }
+    }
+
+    let text: String = characters
+      .iter()
+      .map(|(_, character, _)| *character)
+      .collect();
+    let text = text.trim().to_string();
+    if text.is_empty() {
+      return Ok(None);
+    }
+
+    Ok(Some(TextLine {
+      line: Line {
+        direction: TextDirection::of(&text),
+        text,
+        bounding_box: *area,
+        baseline: None,
+        confidence: confidence * region.score,
+      },
+      words,
+    }))
+  }
+}
+
+#[cfg(feature = "onnx")]
+impl OcrEngine for OnnxEngine {
+  fn recognise(
+    &mut self,
+    buffer: &FrameBuffer,
+    settings: &OcrSettings,
+    _page: i32,
+  ) -> Result<Recognition, MessageError> {
+    let regions = self.detector.detect(buffer)?;
+
+    let mut text_lines = vec![];
+    for region in &regions {
+      if let Some(text_line) = self.read_line(buffer, region, text_lines.len() as u32 + 1)? {
+        text_lines.push(text_line);
+      }
+    }
+
+    let text = text_lines
+      .iter()
+      .map(|text_line| text_line.line.text.as_str())
+      .collect::<Vec<&str>>()
+      .join("\n");
+    let words = text_lines
+      .iter()
+      .flat_map(|text_line| text_line.words.iter().cloned())
+      .collect();
+    let lines = text_lines
+      .iter()
+      .map(|text_line| text_line.line.clone())
+      .collect();
+    let blocks = match text_lines.first() {
+      Some(first) if settings.layout => {
+        let bounding_box = text_lines
+          .iter()
+          .fold(first.line.bounding_box, |bounding_box, text_line| {
+            bounding_box.union(&text_line.line.bounding_box)
+          });
+        vec![Block {
+          bounding_box,
+          paragraphs: vec![Paragraph {
+            bounding_box,
+            lines: text_lines,
+          }],
+        }]
+      }
+      _ => vec![],
+    };
+
+    Ok(Recognition {
+      text,
+      hocr: None,
+      words,
+      lines,
+      blocks,
+    })
+  }
+}
+
+#[cfg(not(feature = "onnx"))]
+impl OnnxEngine {
+  pub fn new(parameters: &OnnxParameters, _device: Device) -> Result<Self, MessageError> {
+    Err(MessageError::ParameterValueError(format!(
+      "Unable to load the ONNX model {}: the worker is built without the onnx feature",
+      parameters.detection_model
+    )))
+  }
+}
+
+#[cfg(not(feature = "onnx"))]
+impl OcrEngine for OnnxEngine {
+  fn recognise(
+    &mut self,
+    _buffer: &FrameBuffer,
+    _settings: &OcrSettings,
+    _page: i32,
+  ) -> Result<Recognition, MessageError> {
+    Ok(Recognition::default())
+  }
+}
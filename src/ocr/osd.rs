@@ -0,0 +1,112 @@
+use super::tess_base_api::TessBaseApi;
+use super::{check_languages, FrameBuffer, OcrEngineMode, OcrSettings, PageSegmentationMode};
+use mcai_worker_sdk::MessageError;
+
+/// Minimal OSD confidence to trust the detected orientation
+const MIN_ORIENTATION_CONFIDENCE: f32 = 2.0;
+
+/// Script and orientation detected by Tesseract OSD
+#[derive(Clone, Debug)]
+pub struct OrientationScript {
+  /// Clockwise rotation correcting the orientation, in degrees
+  pub rotation: u32,
+  pub orientation_confidence: f32,
+  pub script: String,
+  pub script_confidence: f32,
+}
+
+impl OrientationScript {
+  /// Rotation to apply, none when the orientation detection is not reliable
+  pub fn reliable_rotation(&self) -> Option<u32> {
+    if self.orientation_confidence >= MIN_ORIENTATION_CONFIDENCE {
+      Some(self.rotation)
+    } else {
+      None
+    }
+  }
+
+  /// Tesseract script model recognising the detected script
+  pub fn language(&self) -> String {
+    match self.script.as_str() {
+      "Han" => "script/HanS".to_string(),
+      "Korean" => "script/Hangul".to_string(),
+      script => format!("script/{}", script),
+    }
+  }
+}
+
+pub fn detect_orientation_script(
+  buffer: &FrameBuffer,
+  tessdata_path: Option<&str>,
+) -> Result<Option<OrientationScript>, MessageError> {
+  let mut api = TessBaseApi::new(tessdata_path, "osd", OcrEngineMode::Default, &[])?;
+  api.set_page_segmentation_mode(PageSegmentationMode::OsdOnly);
+  api.set_image(buffer);
+
+  Ok(api.detect_orientation_script().map(
+    |(orientation, orientation_confidence, script, script_confidence)| OrientationScript {
+      rotation: ((360 - orientation.rem_euclid(360)) % 360) as u32,
+      orientation_confidence,
+      script,
+      script_confidence,
+    },
+  ))
+}
+
+/// Detection of the script and orientation over the first processed frames,
+/// keeping the most confident script.
+#[derive(Debug)]
+pub struct AutoLanguage {
+  tessdata_path: Option<String>,
+  remaining_frames: u32,
+  best: Option<OrientationScript>,
+}
+
+impl AutoLanguage {
+  pub fn new(frames: u32, tessdata_path: Option<String>) -> Self {
+    AutoLanguage {
+      tessdata_path,
+      remaining_frames: frames,
+      best: None,
+    }
+  }
+
+  pub fn detect(&mut self, buffer: &FrameBuffer) -> Result<(), MessageError> {
+    if let Some(detected) = detect_orientation_script(buffer, self.tessdata_path.as_deref())? {
+      let is_better = match &self.best {
+        Some(best) => detected.script_confidence > best.script_confidence,
+        None => true,
+      };
+      if is_better {
+        self.best = Some(detected);
+      }
+    }
+
+    self.remaining_frames = self.remaining_frames.saturating_sub(1);
+    Ok(())
+  }
+
+  pub fn is_done(&self) -> bool {
+    self.remaining_frames == 0
+  }
+
+  /// Most confident detection over the analysed frames
+  pub fn finish(self) -> Option<OrientationScript> {
+    self.best
+  }
+}
+
+/// Settings using the script model of the detected script, when it is installed
+pub fn apply_detected_script(settings: &mut OcrSettings, detected: &OrientationScript) -> bool {
+  let candidate = OcrSettings {
+    tessdata_path: settings.tessdata_path.clone(),
+    language: detected.language(),
+    engine_mode: settings.engine_mode,
+    ..Default::default()
+  };
+  if check_languages(&candidate).is_err() {
+    return false;
+  }
+  settings.language = candidate.language;
+  true
+}
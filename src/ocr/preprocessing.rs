@@ -0,0 +1,727 @@
+use super::{FrameBuffer, OwnedFrame};
+use crate::layout::Line;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+
+/// Thresholding of the binarization
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BinarizationMethod {
+  /// Threshold of the whole frame, separating the two classes of its histogram
+  Otsu,
+  /// Threshold of each pixel, from the mean of its block
+  Adaptive,
+}
+
+impl Default for BinarizationMethod {
+  fn default() -> Self {
+    BinarizationMethod::Otsu
+  }
+}
+
+/// Thresholding of the frames into black and white pixels, for the text over video backgrounds
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct BinarizationParameters {
+  /// Thresholding method: otsu or adaptive (default: otsu)
+  method: Option<BinarizationMethod>,
+  /// Side of the blocks of the adaptive method, in pixels (default: 31)
+  block_size: Option<u32>,
+  /// Value subtracted from the block mean of the adaptive method, negative to keep only the
+  /// pixels lighter than their block, like the light text over dark backgrounds (default: 10)
+  offset: Option<i32>,
+}
+
+/// Median filter of the frames, removing the film grain recognised as spurious characters
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct DenoiseParameters {
+  /// Side of the window of the median, odd, in pixels (default: 3)
+  size: Option<u32>,
+}
+
+/// Upscaling of the frames with small text, before the OCR
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct UpscaleParameters {
+  /// Height of the text lines aimed at, in pixels (default: 36)
+  text_height: Option<u32>,
+  /// Maximum scale of the frames (default: 4)
+  max_scale: Option<f32>,
+}
+
+impl UpscaleParameters {
+  /// Scale of the frames bringing lines of the height to the target, never downscaling them
+  fn scale(&self, height: f32) -> f32 {
+    let text_height = self.text_height.unwrap_or(36) as f32;
+    let max_scale = self.max_scale.unwrap_or(4.0).max(1.0);
+    if height <= 0.0 {
+      return 1.0;
+    }
+    (text_height / height).clamp(1.0, max_scale)
+  }
+}
+
+/// Sharpening of the frames, like the FFmpeg unsharp filter, for the soft upscaled text
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct UnsharpParameters {
+  /// Side of the blur matrix, odd, in pixels (default: 5)
+  size: Option<u32>,
+  /// Amount of the difference with the blurred frame added to the frame, negative to blur it
+  /// (default: 1)
+  amount: Option<f32>,
+}
+
+/// Morphological operation on the pixel values of the binarized frames
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MorphologyOperation {
+  /// Grow the light areas, thinning the dark strokes
+  Dilate,
+  /// Grow the dark areas, reconnecting the dark strokes broken by the compression
+  Erode,
+}
+
+/// Dilation or erosion of the binarized frames, like the FFmpeg dilation and erosion filters
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct MorphologyParameters {
+  operation: MorphologyOperation,
+  /// Side of the square kernel, odd, in pixels (default: 3)
+  size: Option<u32>,
+}
+
+/// Negation of the frames, Tesseract reading better the dark text on light backgrounds
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Invert {
+  Never,
+  Always,
+  /// Negate the frames darker than the middle gray, like the white on black credits
+  Auto,
+}
+
+impl Default for Invert {
+  fn default() -> Self {
+    Invert::Never
+  }
+}
+
+/// Channel of the frames recognised alone, the colored text over a colored background often
+/// standing out in one of them
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+  Red,
+  Green,
+  Blue,
+  Luma,
+}
+
+/// Image processing steps of the frames, from the parameters of the job
+#[derive(Debug, Default)]
+pub struct PreprocessingSettings {
+  pub channel: Option<Channel>,
+  pub denoise: Option<DenoiseParameters>,
+  /// Values of the channels after the contrast, brightness and gamma adjustment
+  pub levels: Option<[u8; 256]>,
+  pub upscale: Option<UpscaleParameters>,
+  pub unsharp: Option<UnsharpParameters>,
+  pub binarization: Option<BinarizationParameters>,
+  pub morphology: Option<MorphologyParameters>,
+  pub invert: Invert,
+}
+
+/// Image processing of the frames before the OCR
+#[derive(Debug, Default)]
+pub struct Preprocessing {
+  settings: PreprocessingSettings,
+  /// Scale of the upscaling, estimated on the first frame then from the recognised lines
+  scale: Option<f32>,
+}
+
+/// Values of the channels adjusted like the FFmpeg eq filter, the contrast being applied around
+/// the middle gray, then the brightness added, then the gamma correction
+/// (defaults: 1, 0 and 1), None when nothing is adjusted
+pub fn levels(
+  contrast: Option<f32>,
+  brightness: Option<f32>,
+  gamma: Option<f32>,
+) -> Result<Option<[u8; 256]>, MessageError> {
+  if contrast.is_none() && brightness.is_none() && gamma.is_none() {
+    return Ok(None);
+  }
+  let (contrast, brightness, gamma) = (
+    contrast.unwrap_or(1.0),
+    brightness.unwrap_or(0.0),
+    gamma.unwrap_or(1.0),
+  );
+  if gamma <= 0.0 {
+    return Err(MessageError::ParameterValueError(format!(
+      "Invalid gamma {}, it must be positive",
+      gamma
+    )));
+  }
+
+  let mut levels = [0; 256];
+  for (value, level) in levels.iter_mut().enumerate() {
+    let adjusted = ((value as f32 / 255.0 - 0.5) * contrast + 0.5 + brightness).clamp(0.0, 1.0);
+    *level = (adjusted.powf(1.0 / gamma) * 255.0).round() as u8;
+  }
+  Ok(Some(levels))
+}
+
+/// Frame with each channel value replaced by its level
+fn apply_levels(buffer: &FrameBuffer, levels: &[u8; 256]) -> OwnedFrame {
+  let line_size = (buffer.width * buffer.bytes_per_pixel) as usize;
+  let mut data = Vec::with_capacity(line_size * buffer.height as usize);
+  for y in 0..buffer.height as usize {
+    let offset = y * buffer.bytes_per_line as usize;
+    data.extend(
+      buffer.data[offset..offset + line_size]
+        .iter()
+        .map(|value| levels[*value as usize]),
+    );
+  }
+  OwnedFrame::new(data, buffer.width, buffer.height, buffer.bytes_per_pixel)
+}
+
+/// Luminance of the pixels, one byte per pixel
+pub fn luma(buffer: &FrameBuffer) -> Vec<u8> {
+  let bytes_per_pixel = buffer.bytes_per_pixel as usize;
+  let mut luma = Vec::with_capacity((buffer.width * buffer.height) as usize);
+  for y in 0..buffer.height as usize {
+    let line = &buffer.data[y * buffer.bytes_per_line as usize..];
+    for x in 0..buffer.width as usize {
+      let pixel = &line[x * bytes_per_pixel..];
+      luma.push(if bytes_per_pixel >= 3 {
+        ((299 * pixel[0] as u32 + 587 * pixel[1] as u32 + 114 * pixel[2] as u32) / 1000) as u8
+      } else {
+        pixel[0]
+      });
+    }
+  }
+  luma
+}
+
+/// Threshold maximizing the variance between the dark and the light pixels
+fn otsu_threshold(luma: &[u8]) -> u8 {
+  let mut histogram = [0u64; 256];
+  for value in luma {
+    histogram[*value as usize] += 1;
+  }
+  let total = luma.len() as f64;
+  let sum: f64 = histogram
+    .iter()
+    .enumerate()
+    .map(|(value, count)| value as f64 * *count as f64)
+    .sum();
+
+  let (mut dark_count, mut dark_sum) = (0.0, 0.0);
+  let (mut threshold, mut best_variance) = (0, 0.0);
+  for (value, count) in histogram.iter().enumerate() {
+    dark_count += *count as f64;
+    dark_sum += value as f64 * *count as f64;
+    let light_count = total - dark_count;
+    if dark_count == 0.0 || light_count == 0.0 {
+      continue;
+    }
+    let dark_mean = dark_sum / dark_count;
+    let light_mean = (sum - dark_sum) / light_count;
+    let variance = dark_count * light_count * (dark_mean - light_mean).powi(2);
+    if variance > best_variance {
+      best_variance = variance;
+      threshold = value;
+    }
+  }
+  threshold as u8
+}
+
+/// Mean of the values around each one, over a window of `2 * radius + 1` values shrunk
+/// at the edges, the values being `step` apart
+fn box_blur_line(
+  values: &mut [f32],
+  count: usize,
+  step: usize,
+  radius: usize,
+  line: &mut Vec<f32>,
+) {
+  line.clear();
+  line.extend((0..count).map(|index| values[index * step]));
+  let mut sum: f32 = line[..radius.min(count)].iter().sum();
+  for index in 0..count {
+    if index + radius < count {
+      sum += line[index + radius];
+    }
+    if index > radius {
+      sum -= line[index - radius - 1];
+    }
+    let window = (index + radius + 1).min(count) - index.saturating_sub(radius);
+    values[index * step] = sum / window as f32;
+  }
+}
+
+impl DenoiseParameters {
+  fn denoise(&self, buffer: &FrameBuffer) -> OwnedFrame {
+    let (width, height) = (buffer.width as usize, buffer.height as usize);
+    let bytes_per_pixel = buffer.bytes_per_pixel as usize;
+    let radius = (self.size.unwrap_or(3).max(3) / 2) as usize;
+
+    let mut data = Vec::with_capacity(width * height * bytes_per_pixel);
+    let mut window = Vec::with_capacity((2 * radius + 1).pow(2));
+    for y in 0..height {
+      let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+      for x in 0..width {
+        let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+        for channel in 0..bytes_per_pixel {
+          window.clear();
+          for window_y in top..bottom {
+            let line = &buffer.data[window_y * buffer.bytes_per_line as usize..];
+            window.extend((left..right).map(|window_x| line[window_x * bytes_per_pixel + channel]));
+          }
+          let middle = window.len() / 2;
+          data.push(*window.select_nth_unstable(middle).1);
+        }
+      }
+    }
+
+    OwnedFrame::new(data, buffer.width, buffer.height, buffer.bytes_per_pixel)
+  }
+}
+
+impl MorphologyParameters {
+  /// Maximum or minimum of the pixels around each one, in two passes of the kernel lines
+  fn apply(&self, buffer: &FrameBuffer) -> OwnedFrame {
+    let (width, height) = (buffer.width as usize, buffer.height as usize);
+    let bytes_per_pixel = buffer.bytes_per_pixel as usize;
+    let radius = (self.size.unwrap_or(3).max(3) / 2) as usize;
+    let extremum = |values: &mut dyn Iterator<Item = u8>| match self.operation {
+      MorphologyOperation::Dilate => values.max().unwrap_or(0),
+      MorphologyOperation::Erode => values.min().unwrap_or(0),
+    };
+
+    let mut rows = Vec::with_capacity(width * height * bytes_per_pixel);
+    for y in 0..height {
+      let line = &buffer.data[y * buffer.bytes_per_line as usize..];
+      for x in 0..width {
+        let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+        for channel in 0..bytes_per_pixel {
+          rows.push(extremum(
+            &mut (left..right).map(|window_x| line[window_x * bytes_per_pixel + channel]),
+          ));
+        }
+      }
+    }
+
+    let line_size = width * bytes_per_pixel;
+    let mut data = Vec::with_capacity(rows.len());
+    for y in 0..height {
+      let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+      for index in 0..line_size {
+        data.push(extremum(
+          &mut (top..bottom).map(|window_y| rows[window_y * line_size + index]),
+        ));
+      }
+    }
+
+    OwnedFrame::new(data, buffer.width, buffer.height, buffer.bytes_per_pixel)
+  }
+}
+
+impl UnsharpParameters {
+  fn sharpen(&self, buffer: &FrameBuffer) -> OwnedFrame {
+    let (width, height) = (buffer.width as usize, buffer.height as usize);
+    let bytes_per_pixel = buffer.bytes_per_pixel as usize;
+    let radius = (self.size.unwrap_or(5).max(3) / 2) as usize;
+    let amount = self.amount.unwrap_or(1.0);
+
+    let mut data = Vec::with_capacity(width * height * bytes_per_pixel);
+    for y in 0..height {
+      let offset = y * buffer.bytes_per_line as usize;
+      data.extend_from_slice(&buffer.data[offset..offset + width * bytes_per_pixel]);
+    }
+
+    let mut line = Vec::with_capacity(width.max(height));
+    for channel in 0..bytes_per_pixel {
+      let mut blurred: Vec<f32> = data
+        .iter()
+        .skip(channel)
+        .step_by(bytes_per_pixel)
+        .map(|value| *value as f32)
+        .collect();
+      for y in 0..height {
+        box_blur_line(&mut blurred[y * width..], width, 1, radius, &mut line);
+      }
+      for x in 0..width {
+        box_blur_line(&mut blurred[x..], height, width, radius, &mut line);
+      }
+
+      for (index, blurred) in blurred.iter().enumerate() {
+        let value = &mut data[index * bytes_per_pixel + channel];
+        let original = *value as f32;
+        *value = (original + amount * (original - blurred))
+          .round()
+          .clamp(0.0, 255.0) as u8;
+      }
+    }
+
+    OwnedFrame::new(data, buffer.width, buffer.height, buffer.bytes_per_pixel)
+  }
+}
+
+impl BinarizationParameters {
+  fn binarize(&self, buffer: &FrameBuffer) -> OwnedFrame {
+    let (width, height) = (buffer.width as usize, buffer.height as usize);
+    let luma = luma(buffer);
+
+    let data = match self.method.unwrap_or_default() {
+      BinarizationMethod::Otsu => {
+        let threshold = otsu_threshold(&luma);
+        luma
+          .iter()
+          .map(|value| if *value > threshold { 255 } else { 0 })
+          .collect()
+      }
+      BinarizationMethod::Adaptive => {
+        let radius = (self.block_size.unwrap_or(31).max(3) / 2) as usize;
+        let offset = self.offset.unwrap_or(10) as i64;
+
+        // sums of the pixels above and left of each position
+        let mut integral = vec![0u64; (width + 1) * (height + 1)];
+        for y in 0..height {
+          let mut line_sum = 0;
+          for x in 0..width {
+            line_sum += luma[y * width + x] as u64;
+            integral[(y + 1) * (width + 1) + x + 1] = integral[y * (width + 1) + x + 1] + line_sum;
+          }
+        }
+
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+          let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+          for x in 0..width {
+            let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let sum = integral[bottom * (width + 1) + right] + integral[top * (width + 1) + left]
+              - integral[top * (width + 1) + right]
+              - integral[bottom * (width + 1) + left];
+            let mean = (sum / ((bottom - top) * (right - left)) as u64) as i64;
+            data.push(if luma[y * width + x] as i64 > mean - offset {
+              255
+            } else {
+              0
+            });
+          }
+        }
+        data
+      }
+    };
+
+    OwnedFrame::new(data, buffer.width, buffer.height, 1)
+  }
+}
+
+impl FrameBuffer<'_> {
+  /// Copy of a channel of the frame, None for the gray frames
+  fn channel(&self, channel: Channel) -> Option<OwnedFrame> {
+    if self.bytes_per_pixel < 3 {
+      return None;
+    }
+    let index = match channel {
+      Channel::Red => 0,
+      Channel::Green => 1,
+      Channel::Blue => 2,
+      Channel::Luma => return Some(OwnedFrame::new(luma(self), self.width, self.height, 1)),
+    };
+
+    let mut data = Vec::with_capacity((self.width * self.height) as usize);
+    for y in 0..self.height as usize {
+      let line = &self.data[y * self.bytes_per_line as usize..];
+      data.extend(
+        line
+          .chunks_exact(self.bytes_per_pixel as usize)
+          .take(self.width as usize)
+          .map(|pixel| pixel[index]),
+      );
+    }
+    Some(OwnedFrame::new(data, self.width, self.height, 1))
+  }
+
+  /// Negative copy of the frame
+  fn inverted(&self) -> OwnedFrame {
+    let line_size = (self.width * self.bytes_per_pixel) as usize;
+    let mut data = Vec::with_capacity(line_size * self.height as usize);
+    for y in 0..self.height as usize {
+      let offset = y * self.bytes_per_line as usize;
+      data.extend(
+        self.data[offset..offset + line_size]
+          .iter()
+          .map(|value| 255 - value),
+      );
+    }
+    OwnedFrame::new(data, self.width, self.height, self.bytes_per_pixel)
+  }
+
+  /// Copy of the frame resized with a bilinear interpolation
+  pub(super) fn resized(&self, width: i32, height: i32) -> OwnedFrame {
+    let bytes_per_pixel = self.bytes_per_pixel as usize;
+    let (source_width, source_height) = (self.width as usize, self.height as usize);
+    let x_ratio = source_width as f32 / width as f32;
+    let y_ratio = source_height as f32 / height as f32;
+
+    let mut data = Vec::with_capacity(width as usize * height as usize * bytes_per_pixel);
+    for y in 0..height as usize {
+      let source_y = ((y as f32 + 0.5) * y_ratio - 0.5).max(0.0);
+      let top = (source_y as usize).min(source_height - 1);
+      let bottom = (top + 1).min(source_height - 1);
+      let y_weight = source_y - top as f32;
+      let (top_line, bottom_line) = (
+        &self.data[top * self.bytes_per_line as usize..],
+        &self.data[bottom * self.bytes_per_line as usize..],
+      );
+
+      for x in 0..width as usize {
+        let source_x = ((x as f32 + 0.5) * x_ratio - 0.5).max(0.0);
+        let left = (source_x as usize).min(source_width - 1);
+        let right = (left + 1).min(source_width - 1);
+        let x_weight = source_x - left as f32;
+
+        for channel in 0..bytes_per_pixel {
+          let value = |line: &[u8], x: usize| line[x * bytes_per_pixel + channel] as f32;
+          let top_value =
+            value(top_line, left) * (1.0 - x_weight) + value(top_line, right) * x_weight;
+          let bottom_value =
+            value(bottom_line, left) * (1.0 - x_weight) + value(bottom_line, right) * x_weight;
+          data.push((top_value * (1.0 - y_weight) + bottom_value * y_weight).round() as u8);
+        }
+      }
+    }
+
+    OwnedFrame::new(data, width, height, self.bytes_per_pixel)
+  }
+
+  /// Copy of the frame resized to the width or the height, or both, keeping its aspect ratio
+  /// when only one of them is given
+  pub fn scaled_to(&self, width: Option<u32>, height: Option<u32>) -> OwnedFrame {
+    let (source_width, source_height) = (self.width.max(1) as i64, self.height.max(1) as i64);
+    let (width, height) = match (width, height) {
+      (Some(width), Some(height)) => (width as i64, height as i64),
+      (Some(width), None) => (width as i64, source_height * width as i64 / source_width),
+      (None, Some(height)) => (source_width * height as i64 / source_height, height as i64),
+      (None, None) => (source_width, source_height),
+    };
+    self.resized(width.max(1) as i32, height.max(1) as i32)
+  }
+
+  /// Copy of the frame converted to a single luminance channel
+  pub fn grayscale(&self) -> OwnedFrame {
+    OwnedFrame::new(luma(self), self.width, self.height, 1)
+  }
+
+  /// Copy of the interlaced frame keeping the lines of its first field, the lines of the other
+  /// field being interpolated from their neighbours to remove the combing through the text,
+  /// `first_line` being the line of the decoded frame at the top of the buffer, like the top of
+  /// the cropped region of interest
+  pub fn deinterlaced(&self, top_field_first: bool, first_line: u32) -> OwnedFrame {
+    let (height, line_size) = (
+      self.height as usize,
+      (self.width * self.bytes_per_pixel) as usize,
+    );
+    let line = |y: usize| {
+      let offset = y * self.bytes_per_line as usize;
+      &self.data[offset..offset + line_size]
+    };
+    let kept_parity = ((if top_field_first { 0 } else { 1 }) + first_line as usize) % 2;
+
+    let mut data = Vec::with_capacity(line_size * height);
+    for y in 0..height {
+      if y % 2 == kept_parity || height < 2 {
+        data.extend_from_slice(line(y));
+        continue;
+      }
+      let above = if y > 0 { line(y - 1) } else { line(y + 1) };
+      let below = if y + 1 < height { line(y + 1) } else { above };
+      data.extend(
+        above
+          .iter()
+          .zip(below.iter())
+          .map(|(above, below)| ((*above as u16 + *below as u16) / 2) as u8),
+      );
+    }
+
+    OwnedFrame::new(data, self.width, self.height, self.bytes_per_pixel)
+  }
+}
+
+impl Preprocessing {
+  pub fn new(settings: PreprocessingSettings) -> Self {
+    Preprocessing {
+      settings,
+      scale: None,
+    }
+  }
+
+  /// Size of the processed frame side, upscaled or not
+  pub fn scaled(&self, size: i32) -> i32 {
+    match self.scale {
+      Some(scale) if self.settings.upscale.is_some() => (size as f32 * scale).round() as i32,
+      _ => size,
+    }
+  }
+
+  /// Update the upscaling from the height of the lines recognised in the processed frame
+  pub fn measure(&mut self, lines: &[Line]) {
+    let (upscale, scale) = match (&self.settings.upscale, self.scale) {
+      (Some(upscale), Some(scale)) => (upscale, scale),
+      _ => return,
+    };
+    let mut heights: Vec<u32> = lines.iter().map(|line| line.bounding_box.height).collect();
+    if heights.is_empty() {
+      return;
+    }
+    let middle = heights.len() / 2;
+    let height = *heights.select_nth_unstable(middle).1 as f32 / scale;
+
+    let measured_scale = upscale.scale(height);
+    // do not resize each frame for small changes of the recognised lines
+    if (measured_scale - scale).abs() > 0.1 * scale {
+      self.scale = Some(measured_scale);
+    }
+  }
+
+  /// Processed copy of the frame: reduced to a channel, denoised, adjusted, upscaled, sharpened, binarized,
+  /// dilated or eroded then inverted, None when no processing is set
+  pub fn apply(&mut self, buffer: &FrameBuffer) -> Option<OwnedFrame> {
+    if let (Some(upscale), None) = (&self.settings.upscale, self.scale) {
+      // until the lines are measured, the frame is assumed to be a band of text
+      self.scale = Some(upscale.scale(buffer.height as f32 / 2.0));
+    }
+    let (width, height) = (self.scaled(buffer.width), self.scaled(buffer.height));
+
+    let mut processed: Option<OwnedFrame> = None;
+    // steps returning None leave the frame unchanged
+    let mut process = |step: &dyn Fn(&FrameBuffer) -> Option<OwnedFrame>| {
+      let frame = match &processed {
+        Some(frame) => step(&frame.buffer()),
+        None => step(buffer),
+      };
+      if frame.is_some() {
+        processed = frame;
+      }
+    };
+
+    if let Some(channel) = self.settings.channel {
+      process(&|buffer| buffer.channel(channel));
+    }
+    if let Some(denoise) = &self.settings.denoise {
+      process(&|buffer| Some(denoise.denoise(buffer)));
+    }
+    if let Some(levels) = &self.settings.levels {
+      process(&|buffer| Some(apply_levels(buffer, levels)));
+    }
+    if width != buffer.width || height != buffer.height {
+      process(&|buffer| Some(buffer.resized(width, height)));
+    }
+    if let Some(unsharp) = &self.settings.unsharp {
+      process(&|buffer| Some(unsharp.sharpen(buffer)));
+    }
+    if let Some(binarization) = &self.settings.binarization {
+      process(&|buffer| Some(binarization.binarize(buffer)));
+    }
+    if let Some(morphology) = &self.settings.morphology {
+      process(&|buffer| Some(morphology.apply(buffer)));
+    }
+    match self.settings.invert {
+      Invert::Never => {}
+      Invert::Always => process(&|buffer| Some(buffer.inverted())),
+      Invert::Auto => process(&|buffer| {
+        let luma = luma(buffer);
+        let mean = luma.iter().map(|value| *value as u64).sum::<u64>() / luma.len().max(1) as u64;
+        if mean < 128 {
+          Some(buffer.inverted())
+        } else {
+          None
+        }
+      }),
+    }
+    processed
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Gray frame one pixel wide, one value per line
+  fn column(values: &[u8]) -> FrameBuffer<'_> {
+    FrameBuffer {
+      data: values,
+      width: 1,
+      height: values.len() as i32,
+      bytes_per_pixel: 1,
+      bytes_per_line: 1,
+    }
+  }
+
+  #[test]
+  fn otsu_threshold_splits_the_text_from_the_background() {
+    let mut values = vec![20; 300];
+    values.extend(vec![35; 100]);
+    values.extend(vec![190; 60]);
+    values.extend(vec![230; 40]);
+
+    let threshold = otsu_threshold(&values);
+    assert!((35..190).contains(&threshold), "threshold {}", threshold);
+    assert_eq!(otsu_threshold(&[128; 64]), 0);
+  }
+
+  #[test]
+  fn luma_of_the_rgb_pixels() {
+    let pixels = [255, 0, 0, 0, 255, 0, 0, 0, 255, 200, 200, 200];
+    let frame = FrameBuffer {
+      data: &pixels,
+      width: 4,
+      height: 1,
+      bytes_per_pixel: 3,
+      bytes_per_line: 12,
+    };
+
+    assert_eq!(luma(&frame), vec![76, 149, 29, 200]);
+  }
+
+  #[test]
+  fn deinterlaced_keeps_the_first_field() {
+    let frame = column(&[10, 200, 30, 220]);
+
+    assert_eq!(frame.deinterlaced(true, 0).buffer().data, &[10, 20, 30, 30]);
+    assert_eq!(
+      frame.deinterlaced(false, 0).buffer().data,
+      &[200, 200, 210, 220]
+    );
+  }
+
+  #[test]
+  fn deinterlaced_follows_the_field_parity_of_the_cropped_region() {
+    // the region starts on line 1 of the decoded frame, a line of the bottom field
+    let frame = column(&[200, 30, 220, 50]);
+
+    assert_eq!(frame.deinterlaced(true, 1).buffer().data, &[30, 30, 40, 50]);
+    assert_eq!(
+      frame.deinterlaced(false, 1).buffer().data,
+      &[200, 210, 220, 220]
+    );
+  }
+
+  #[test]
+  fn scaled_to_keeps_the_aspect_ratio() {
+    let data = vec![0; 8 * 4];
+    let frame = FrameBuffer {
+      data: &data,
+      width: 8,
+      height: 4,
+      bytes_per_pixel: 1,
+      bytes_per_line: 8,
+    };
+
+    let scaled = frame.scaled_to(Some(4), None);
+    assert_eq!((scaled.buffer().width, scaled.buffer().height), (4, 2));
+    let scaled = frame.scaled_to(None, Some(8));
+    assert_eq!((scaled.buffer().width, scaled.buffer().height), (16, 8));
+    let scaled = frame.scaled_to(Some(3), Some(3));
+    assert_eq!((scaled.buffer().width, scaled.buffer().height), (3, 3));
+  }
+}
@@ -0,0 +1,131 @@
+use super::{
+  create_engine, recognise, FrameBuffer, Language, OcrEngine, OcrSettings, PageSegmentationMode,
+  Recognition,
+};
+use crate::region::{Rectangle, SourceMapping};
+use mcai_worker_sdk::{JsonSchema, MessageError, RegionOfInterest};
+
+/// Region of the frame recognised with its own language,
+/// like an Arabic ticker under an English scoreboard
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct RegionParameters {
+  /// Area of the region in the source frame
+  region: RegionOfInterest,
+  /// Language of the region, or a list of languages (default: language of the job)
+  language: Option<Language>,
+  /// Tesseract page segmentation mode of the region (default: page segmentation mode of the job)
+  #[serde(alias = "psm")]
+  page_segmentation_mode: Option<PageSegmentationMode>,
+}
+
+impl RegionParameters {
+  pub fn language(&self) -> Option<&Language> {
+    self.language.as_ref()
+  }
+}
+
+/// Engine of a region, initialized with the language of the region
+#[derive(Debug)]
+pub struct RegionRecognizer {
+  region: RegionOfInterest,
+  settings: OcrSettings,
+  engine: Box<dyn OcrEngine>,
+}
+
+impl RegionRecognizer {
+  pub fn new(parameters: &RegionParameters, settings: &OcrSettings) -> Result<Self, MessageError> {
+    let mut settings = settings.clone();
+    if let Some(language) = &parameters.language {
+      settings.language = language.to_tesseract();
+    }
+    if let Some(page_segmentation_mode) = parameters.page_segmentation_mode {
+      settings.page_segmentation_mode = Some(page_segmentation_mode);
+    }
+    let engine = create_engine(&settings)?;
+
+    Ok(RegionRecognizer {
+      region: parameters.region.clone(),
+      settings,
+      engine,
+    })
+  }
+}
+
+impl<'a> FrameBuffer<'a> {
+  /// Pixels of the area, sharing the frame data
+  pub fn cropped(&self, area: &Rectangle) -> FrameBuffer<'a> {
+    let offset = area.top as usize * self.bytes_per_line as usize
+      + area.left as usize * self.bytes_per_pixel as usize;
+    FrameBuffer {
+      data: &self.data[offset..],
+      width: area.width as i32,
+      height: area.height as i32,
+      bytes_per_pixel: self.bytes_per_pixel,
+      bytes_per_line: self.bytes_per_line,
+    }
+  }
+}
+
+/// Run the OCR on each region of the frame with its engine, the text being located in the
+/// processed image as with a single recognition
+pub fn recognise_regions(
+  recognizers: &mut [RegionRecognizer],
+  buffer: &FrameBuffer,
+  mapping: &SourceMapping,
+  frame_width: u32,
+  frame_height: u32,
+  page: i32,
+) -> Result<Recognition, MessageError> {
+  let mut recognition = Recognition::default();
+  for recognizer in recognizers {
+    let area = Rectangle::from_region_of_interest(&recognizer.region, frame_width, frame_height);
+    let area = match mapping.processed_rectangle(area).and_then(|area| {
+      area.intersection(&Rectangle {
+        left: 0,
+        top: 0,
+        width: buffer.width as u32,
+        height: buffer.height as u32,
+      })
+    }) {
+      Some(area) => area,
+      None => continue,
+    };
+
+    let region_recognition = recognise(
+      recognizer.engine.as_mut(),
+      &buffer.cropped(&area),
+      &recognizer.settings,
+      page,
+    )?;
+    // locate the text in the processed image, the region being only moved
+    let offset = SourceMapping {
+      processed_width: area.width,
+      processed_height: area.height,
+      source_area: area,
+      rotation: 0,
+    };
+    if !recognition.text.is_empty() && !recognition.text.ends_with('\n') {
+      recognition.text.push('\n');
+    }
+    recognition.append(Recognition {
+      text: region_recognition.text,
+      hocr: region_recognition.hocr,
+      words: region_recognition
+        .words
+        .into_iter()
+        .map(|word| word.into_source_frame(&offset))
+        .collect(),
+      lines: region_recognition
+        .lines
+        .into_iter()
+        .map(|line| line.into_source_frame(&offset))
+        .collect(),
+      blocks: region_recognition
+        .blocks
+        .into_iter()
+        .map(|block| block.into_source_frame(&offset))
+        .collect(),
+    });
+  }
+  Ok(recognition)
+}
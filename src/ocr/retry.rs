@@ -0,0 +1,56 @@
+use super::{FrameBuffer, OwnedFrame, Recognition};
+use crate::layout::mean_confidence;
+use mcai_worker_sdk::JsonSchema;
+
+/// Recognition of the low confidence frames again at twice their size, within a budget
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct LowConfidenceRetryParameters {
+  /// Mean confidence of the words of the frame below which it is recognised again,
+  /// from 0 to 100 (default: 60)
+  threshold: Option<f32>,
+  /// Maximum number of frames recognised again during the job (default: 100)
+  max_retries: Option<u32>,
+}
+
+/// Retries left in the budget of the job
+#[derive(Debug)]
+pub struct LowConfidenceRetry {
+  threshold: f32,
+  remaining: u32,
+}
+
+impl LowConfidenceRetry {
+  pub fn new(parameters: &LowConfidenceRetryParameters) -> Self {
+    LowConfidenceRetry {
+      threshold: parameters.threshold.unwrap_or(60.0),
+      remaining: parameters.max_retries.unwrap_or(100),
+    }
+  }
+
+  /// Whether the frame with text is recognised again, the retry being counted in the budget
+  pub fn should_retry(&mut self, recognition: &Recognition) -> bool {
+    match mean_confidence(&recognition.words) {
+      Some(confidence) if confidence < self.threshold && self.remaining > 0 => {
+        self.remaining -= 1;
+        true
+      }
+      _ => false,
+    }
+  }
+
+  /// Whether the recognition of the retry scores higher than the first one
+  pub fn is_better(first: &Recognition, retried: &Recognition) -> bool {
+    match (
+      mean_confidence(&first.words),
+      mean_confidence(&retried.words),
+    ) {
+      (Some(first), Some(retried)) => retried > first,
+      _ => false,
+    }
+  }
+}
+
+/// Frame at twice its size, for the retry
+pub fn upscaled(buffer: &FrameBuffer) -> OwnedFrame {
+  buffer.resized(buffer.width * 2, buffer.height * 2)
+}
@@ -0,0 +1,89 @@
+use super::FrameBuffer;
+use stainless_ffmpeg_sys::{
+  av_display_rotation_get, av_stream_get_side_data, AVPacketSideDataType, AVStream,
+};
+
+/// Frame pixels owned after a transformation, like a rotation
+pub struct OwnedFrame {
+  data: Vec<u8>,
+  width: i32,
+  height: i32,
+  bytes_per_pixel: i32,
+}
+
+impl OwnedFrame {
+  pub(super) fn new(data: Vec<u8>, width: i32, height: i32, bytes_per_pixel: i32) -> Self {
+    OwnedFrame {
+      data,
+      width,
+      height,
+      bytes_per_pixel,
+    }
+  }
+
+  pub fn buffer(&self) -> FrameBuffer<'_> {
+    FrameBuffer {
+      data: &self.data,
+      width: self.width,
+      height: self.height,
+      bytes_per_pixel: self.bytes_per_pixel,
+      bytes_per_line: self.width * self.bytes_per_pixel,
+    }
+  }
+}
+
+/// Clockwise rotation of the display matrix of the stream, like the one of the portrait videos
+/// shot with a phone, rounded to 0, 90, 180 or 270 degrees
+///
+/// # Safety
+///
+/// The stream must be a valid stream of an opened format context
+pub unsafe fn display_rotation(stream: *const AVStream) -> u32 {
+  let matrix = av_stream_get_side_data(
+    stream,
+    AVPacketSideDataType::AV_PKT_DATA_DISPLAYMATRIX,
+    std::ptr::null_mut(),
+  );
+  if matrix.is_null() {
+    return 0;
+  }
+  // the display matrix rotates counterclockwise
+  let rotation = -av_display_rotation_get(matrix as *const i32);
+  if rotation.is_nan() {
+    return 0;
+  }
+  ((rotation / 90.0).round() as i64 * 90).rem_euclid(360) as u32
+}
+
+impl FrameBuffer<'_> {
+  /// Copy of the frame rotated clockwise by 90, 180 or 270 degrees
+  pub fn rotated(&self, rotation: u32) -> OwnedFrame {
+    let (width, height) = (self.width as usize, self.height as usize);
+    let bytes_per_pixel = self.bytes_per_pixel as usize;
+    let (rotated_width, rotated_height) = match rotation {
+      90 | 270 => (height, width),
+      _ => (width, height),
+    };
+
+    let mut data = Vec::with_capacity(rotated_width * rotated_height * bytes_per_pixel);
+    for y in 0..rotated_height {
+      for x in 0..rotated_width {
+        let (source_x, source_y) = match rotation {
+          90 => (y, height - 1 - x),
+          180 => (width - 1 - x, height - 1 - y),
+          270 => (width - 1 - y, x),
+          _ => (x, y),
+        };
+        let offset = source_y * self.bytes_per_line as usize + source_x * bytes_per_pixel;
+        data.extend_from_slice(&self.data[offset..offset + bytes_per_pixel]);
+      }
+    }
+
+    OwnedFrame {
+      data,
+      width: rotated_width as i32,
+      height: rotated_height as i32,
+      bytes_per_pixel: self.bytes_per_pixel,
+    }
+  }
+}
@@ -0,0 +1,52 @@
+use super::FrameBuffer;
+use crate::region::Rectangle;
+use mcai_worker_sdk::MessageError;
+use png::{BitDepth, ColorType, Encoder};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+impl FrameBuffer<'_> {
+  /// Save the area of the buffer as a PNG image
+  pub fn save_png(&self, path: &Path, area: &Rectangle) -> Result<(), MessageError> {
+    let color_type = match self.bytes_per_pixel {
+      1 => ColorType::Grayscale,
+      3 => ColorType::RGB,
+      4 => ColorType::RGBA,
+      bytes_per_pixel => {
+        return Err(MessageError::RuntimeError(format!(
+          "Unable to save a PNG with {} bytes per pixel",
+          bytes_per_pixel
+        )))
+      }
+    };
+
+    // keep the area inside the buffer
+    let left = area.left.min(self.width as u32) as usize;
+    let top = area.top.min(self.height as u32) as usize;
+    let width = (area.width as usize).min(self.width as usize - left);
+    let height = (area.height as usize).min(self.height as usize - top);
+    if width == 0 || height == 0 {
+      return Ok(());
+    }
+
+    let bytes_per_pixel = self.bytes_per_pixel as usize;
+    let mut pixels = Vec::with_capacity(width * height * bytes_per_pixel);
+    for row in top..top + height {
+      let start = row * self.bytes_per_line as usize + left * bytes_per_pixel;
+      pixels.extend_from_slice(&self.data[start..start + width * bytes_per_pixel]);
+    }
+
+    let to_error = |error: &dyn std::fmt::Display| {
+      MessageError::RuntimeError(format!("Unable to write {}: {}", path.display(), error))
+    };
+    let file = File::create(path).map_err(|error| to_error(&error))?;
+    let mut encoder = Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(color_type);
+    encoder.set_depth(BitDepth::Eight);
+    encoder
+      .write_header()
+      .and_then(|mut writer| writer.write_image_data(&pixels))
+      .map_err(|error| to_error(&error))
+  }
+}
@@ -0,0 +1,302 @@
+use super::UserList;
+use mcai_worker_sdk::{warn, JsonSchema, MessageError};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Shortest word corrected by edit distance, the shorter ones matching too many dictionary words
+const MIN_EDITED_LENGTH: usize = 4;
+
+/// Dictionary-based correction of the recognised text
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct SpellCorrectionParameters {
+  /// Directory of the dictionaries, named after the languages of the job like eng.txt,
+  /// with one word per line (Hunspell .dic files are accepted)
+  dictionary_directory: Option<String>,
+  /// Custom dictionary added to the language ones, like the channel and presenter names:
+  /// path of a words file, or the list of words
+  custom_dictionary: Option<UserList>,
+  /// Maximum number of edited characters of a correction, 1 or 2 (default: 1)
+  max_distance: Option<usize>,
+}
+
+/// Spell checker of the job, indexing its dictionary words
+#[derive(Debug, Default)]
+pub struct SpellChecker {
+  words: Vec<String>,
+  known: HashSet<String>,
+  /// Words by their shape, merging the characters confused by the OCR like 0 and o
+  shapes: HashMap<String, Vec<usize>>,
+  /// Words by the strings left after deleting up to max_distance characters
+  deletes: HashMap<String, Vec<usize>>,
+  max_distance: usize,
+}
+
+/// Character standing for the characters with the same shape
+fn shape_character(character: char) -> char {
+  match character {
+    '0' => 'o',
+    '1' | 'i' | '|' => 'l',
+    '5' => 's',
+    '8' => 'b',
+    '2' => 'z',
+    character => character,
+  }
+}
+
+fn shape(word: &str) -> String {
+  word.chars().map(shape_character).collect()
+}
+
+/// Strings left after deleting up to `distance` characters of the word
+fn deletes(word: &str, distance: usize) -> HashSet<String> {
+  let mut strings = HashSet::new();
+  strings.insert(word.to_string());
+  let mut previous = vec![word.to_string()];
+  for _ in 0..distance {
+    let mut next = vec![];
+    for string in &previous {
+      let characters: Vec<char> = string.chars().collect();
+      for index in 0..characters.len() {
+        let deleted: String = characters
+          .iter()
+          .enumerate()
+          .filter(|(position, _)| *position != index)
+          .map(|(_, character)| character)
+          .collect();
+        if strings.insert(deleted.clone()) {
+          next.push(deleted);
+        }
+      }
+    }
+    previous = next;
+  }
+  strings
+}
+
+/// Edit distance between the words, the substitutions of confused characters costing half
+fn weighted_distance(left: &str, right: &str) -> f32 {
+  let left: Vec<char> = left.chars().collect();
+  let right: Vec<char> = right.chars().collect();
+  let mut previous: Vec<f32> = (0..=right.len()).map(|index| index as f32).collect();
+
+  for (left_index, left_character) in left.iter().enumerate() {
+    let mut current = vec![left_index as f32 + 1.0];
+    for (right_index, right_character) in right.iter().enumerate() {
+      let substitution = if left_character == right_character {
+        0.0
+      } else if shape_character(*left_character) == shape_character(*right_character) {
+        0.5
+      } else {
+        1.0
+      };
+      current.push(
+        (previous[right_index] + substitution)
+          .min(previous[right_index + 1] + 1.0)
+          .min(current[right_index] + 1.0),
+      );
+    }
+    previous = current;
+  }
+  previous[right.len()]
+}
+
+/// Correction in the case of the recognised token: upper case, capitalized or lower case
+fn with_case_of(correction: &str, token: &str) -> String {
+  let letters: Vec<char> = token
+    .chars()
+    .filter(|character| character.is_alphabetic())
+    .collect();
+  if letters.len() > 1 && letters.iter().all(|character| character.is_uppercase()) {
+    return correction.to_uppercase();
+  }
+
+  match token.chars().next() {
+    Some(first) if first.is_uppercase() => {
+      let mut characters = correction.chars();
+      characters
+        .next()
+        .map(|first| first.to_uppercase().chain(characters).collect())
+        .unwrap_or_default()
+    }
+    _ => correction.to_string(),
+  }
+}
+
+impl SpellChecker {
+  /// Load the dictionaries of the languages, Tesseract codes joined with + like eng+fra
+  pub fn new(parameters: &SpellCorrectionParameters, language: &str) -> Result<Self, MessageError> {
+    let mut lines = vec![];
+    if let Some(directory) = &parameters.dictionary_directory {
+      for language in language.split('+') {
+        let path = Path::new(directory).join(format!("{}.txt", language));
+        match fs::read_to_string(&path) {
+          Ok(content) => lines.extend(content.lines().map(str::to_string)),
+          Err(error) => warn!(
+            "No spell correction dictionary for {} ({}: {})",
+            language,
+            path.display(),
+            error
+          ),
+        }
+      }
+    }
+    if let Some(custom_dictionary) = &parameters.custom_dictionary {
+      lines.extend(custom_dictionary.items()?);
+    }
+
+    let mut spell_checker = SpellChecker {
+      max_distance: parameters.max_distance.unwrap_or(1).clamp(1, 2),
+      ..Default::default()
+    };
+    for line in lines {
+      // drop the Hunspell affix flags and the frequencies following the word
+      let word = line
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+      if !word.chars().any(char::is_alphabetic) || !spell_checker.known.insert(word.clone()) {
+        continue;
+      }
+
+      let index = spell_checker.words.len();
+      spell_checker
+        .shapes
+        .entry(shape(&word))
+        .or_default()
+        .push(index);
+      for deleted in deletes(&word, spell_checker.max_distance) {
+        spell_checker
+          .deletes
+          .entry(deleted)
+          .or_default()
+          .push(index);
+      }
+      spell_checker.words.push(word);
+    }
+
+    if spell_checker.words.is_empty() {
+      return Err(MessageError::ParameterValueError(
+        "The spell correction dictionaries contain no words".to_string(),
+      ));
+    }
+    Ok(spell_checker)
+  }
+
+  /// Closest dictionary word, None when the word is known or no single word is the closest
+  fn correction(&self, word: &str) -> Option<&str> {
+    if self.known.contains(word) || word.chars().count() < 2 {
+      return None;
+    }
+
+    let mut candidates: HashSet<usize> = self
+      .shapes
+      .get(&shape(word))
+      .cloned()
+      .unwrap_or_default()
+      .into_iter()
+      .collect();
+    if candidates.is_empty() && word.chars().count() >= MIN_EDITED_LENGTH {
+      candidates = deletes(word, self.max_distance)
+        .iter()
+        .filter_map(|deleted| self.deletes.get(deleted))
+        .flatten()
+        .cloned()
+        .collect();
+    }
+
+    let mut best: Option<(f32, &str)> = None;
+    let mut tied = false;
+    for candidate in candidates {
+      let candidate = self.words[candidate].as_str();
+      let distance = weighted_distance(word, candidate);
+      if distance > self.max_distance as f32 {
+        continue;
+      }
+      match best {
+        Some((best_distance, _)) if distance > best_distance => {}
+        Some((best_distance, _)) if distance == best_distance => tied = true,
+        _ => {
+          best = Some((distance, candidate));
+          tied = false;
+        }
+      }
+    }
+    best.filter(|_| !tied).map(|(_, candidate)| candidate)
+  }
+
+  /// Text with the unknown words replaced by their closest dictionary word, keeping their case
+  pub fn correct(&self, text: &str) -> String {
+    let mut corrected = String::with_capacity(text.len());
+    let mut token = String::new();
+
+    let flush = |token: &mut String, corrected: &mut String| {
+      if token.chars().any(char::is_alphabetic) {
+        match self.correction(&token.to_lowercase()) {
+          Some(correction) => corrected.push_str(&with_case_of(correction, token)),
+          None => corrected.push_str(token),
+        }
+      } else {
+        corrected.push_str(token);
+      }
+      token.clear();
+    };
+
+    for character in text.chars() {
+      if character.is_alphanumeric() || character == '|' {
+        token.push(character);
+      } else {
+        flush(&mut token, &mut corrected);
+        corrected.push(character);
+      }
+    }
+    flush(&mut token, &mut corrected);
+    corrected
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn spell_checker(words: &[&str]) -> Result<SpellChecker, MessageError> {
+    let parameters = SpellCorrectionParameters {
+      dictionary_directory: None,
+      custom_dictionary: Some(UserList::Items(
+        words.iter().map(|word| word.to_string()).collect(),
+      )),
+      max_distance: None,
+    };
+    SpellChecker::new(&parameters, "eng")
+  }
+
+  #[test]
+  fn weighted_distances() {
+    assert_eq!(weighted_distance("open", "open"), 0.0);
+    assert_eq!(weighted_distance("0pen", "open"), 0.5);
+    assert_eq!(weighted_distance("weathr", "weather"), 1.0);
+    assert_eq!(weighted_distance("parks", "paris"), 1.0);
+  }
+
+  #[test]
+  fn corrections_keep_the_case_of_the_words() {
+    let spell_checker =
+      spell_checker(&["breaking", "news", "weather/S 120", "paris", "parts"]).unwrap();
+
+    assert_eq!(
+      spell_checker.correct("BREAK1NG NEWS! Weathr in Pari5"),
+      "BREAKING NEWS! Weather in Paris"
+    );
+    // tied between paris and parts, or too short to be edited
+    assert_eq!(spell_checker.correct("Parks nws"), "Parks nws");
+  }
+
+  #[test]
+  fn dictionaries_without_words() {
+    assert!(spell_checker(&["123", ""]).is_err());
+  }
+}
@@ -0,0 +1,308 @@
+use super::{FrameBuffer, OcrEngineMode, PageSegmentationMode};
+use crate::layout::Baseline;
+use crate::region::{Point, Rectangle};
+use mcai_worker_sdk::MessageError;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use tesseract_sys::*;
+
+pub use tesseract_sys::{
+  TessPageIteratorLevel as Level, TessPageIteratorLevel_RIL_BLOCK as BLOCK,
+  TessPageIteratorLevel_RIL_PARA as PARAGRAPH, TessPageIteratorLevel_RIL_TEXTLINE as TEXT_LINE,
+  TessPageIteratorLevel_RIL_WORD as WORD,
+};
+
+/// Take the ownership of a text allocated by Tesseract
+unsafe fn take_text(text: *mut c_char) -> Option<String> {
+  if text.is_null() {
+    return None;
+  }
+  let string = CStr::from_ptr(text).to_string_lossy().into_owned();
+  TessDeleteText(text);
+  Some(string)
+}
+
+fn to_c_string(value: &str) -> Result<CString, MessageError> {
+  CString::new(value).map_err(|error| {
+    MessageError::ParameterValueError(format!("Invalid value {}: {}", value, error))
+  })
+}
+
+/// Owned handle on the Tesseract C API
+#[derive(Debug)]
+pub struct TessBaseApi(*mut TessBaseAPI);
+
+// The handle is owned by a single worker, and only used by one thread at a time
+unsafe impl Send for TessBaseApi {}
+unsafe impl Sync for TessBaseApi {}
+
+impl Drop for TessBaseApi {
+  fn drop(&mut self) {
+    unsafe { TessBaseAPIDelete(self.0) }
+  }
+}
+
+impl TessBaseApi {
+  pub fn new(
+    tessdata_path: Option<&str>,
+    language: &str,
+    engine_mode: OcrEngineMode,
+    variables: &[(String, String)],
+  ) -> Result<Self, MessageError> {
+    let api = TessBaseApi(unsafe { TessBaseAPICreate() });
+    let c_language = to_c_string(language)?;
+    let c_tessdata_path = tessdata_path.map(to_c_string).transpose()?;
+
+    let names = variables
+      .iter()
+      .map(|(name, _)| to_c_string(name))
+      .collect::<Result<Vec<CString>, MessageError>>()?;
+    let values = variables
+      .iter()
+      .map(|(_, value)| to_c_string(value))
+      .collect::<Result<Vec<CString>, MessageError>>()?;
+    let mut name_pointers: Vec<*mut c_char> = names
+      .iter()
+      .map(|name| name.as_ptr() as *mut c_char)
+      .collect();
+    let mut value_pointers: Vec<*mut c_char> = values
+      .iter()
+      .map(|value| value.as_ptr() as *mut c_char)
+      .collect();
+
+    let status = unsafe {
+      TessBaseAPIInit4(
+        api.0,
+        c_tessdata_path
+          .as_ref()
+          .map_or(ptr::null(), |path| path.as_ptr()),
+        c_language.as_ptr(),
+        engine_mode.to_tesseract(),
+        ptr::null_mut(),
+        0,
+        name_pointers.as_mut_ptr(),
+        value_pointers.as_mut_ptr(),
+        variables.len(),
+        0,
+      )
+    };
+    if status != 0 {
+      return Err(MessageError::RuntimeError(format!(
+        "Unable to initialize Tesseract with language {} and engine mode {:?}",
+        language, engine_mode
+      )));
+    }
+    Ok(api)
+  }
+
+  /// Languages whose traineddata file is loaded
+  pub fn loaded_languages(&self) -> Vec<String> {
+    let mut languages = vec![];
+    unsafe {
+      let array = TessBaseAPIGetLoadedLanguagesAsVector(self.0);
+      if array.is_null() {
+        return languages;
+      }
+      let mut index = 0;
+      while !(*array.offset(index)).is_null() {
+        languages.push(
+          CStr::from_ptr(*array.offset(index))
+            .to_string_lossy()
+            .into_owned(),
+        );
+        index += 1;
+      }
+      TessDeleteTextArray(array);
+    }
+    languages
+  }
+
+  pub fn set_page_segmentation_mode(&mut self, mode: PageSegmentationMode) {
+    unsafe { TessBaseAPISetPageSegMode(self.0, mode.to_tesseract()) }
+  }
+
+  pub fn set_image(&mut self, buffer: &FrameBuffer) {
+    unsafe {
+      TessBaseAPISetImage(
+        self.0,
+        buffer.data.as_ptr(),
+        buffer.width,
+        buffer.height,
+        buffer.bytes_per_pixel,
+        buffer.bytes_per_line,
+      )
+    }
+  }
+
+  /// Restrict the recognition to an area of the image, keeping the coordinates of the image
+  pub fn set_rectangle(&mut self, area: &Rectangle) {
+    unsafe {
+      TessBaseAPISetRectangle(
+        self.0,
+        area.left as c_int,
+        area.top as c_int,
+        area.width as c_int,
+        area.height as c_int,
+      )
+    }
+  }
+
+  pub fn recognize(&mut self) -> Result<(), MessageError> {
+    if unsafe { TessBaseAPIRecognize(self.0, ptr::null_mut()) } != 0 {
+      return Err(MessageError::RuntimeError(
+        "Tesseract failed to recognise the frame".to_string(),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Orientation of the page in degrees and its confidence, with the script name and its confidence
+  pub fn detect_orientation_script(&mut self) -> Option<(i32, f32, String, f32)> {
+    let mut orientation: c_int = 0;
+    let mut orientation_confidence: f32 = 0.0;
+    let mut script_name: *const c_char = ptr::null();
+    let mut script_confidence: f32 = 0.0;
+
+    let detected = unsafe {
+      TessBaseAPIDetectOrientationScript(
+        self.0,
+        &mut orientation,
+        &mut orientation_confidence,
+        &mut script_name,
+        &mut script_confidence,
+      )
+    };
+    if detected == 0 || script_name.is_null() {
+      return None;
+    }
+
+    // the script name is owned by Tesseract
+    let script = unsafe { CStr::from_ptr(script_name) }
+      .to_string_lossy()
+      .into_owned();
+    Some((
+      orientation as i32,
+      orientation_confidence,
+      script,
+      script_confidence,
+    ))
+  }
+
+  pub fn get_text(&mut self) -> String {
+    unsafe { take_text(TessBaseAPIGetUTF8Text(self.0)) }.unwrap_or_default()
+  }
+
+  pub fn get_hocr_text(&mut self, page: i32) -> String {
+    unsafe { take_text(TessBaseAPIGetHOCRText(self.0, page as c_int)) }.unwrap_or_default()
+  }
+
+  pub fn get_tsv_text(&mut self, page: i32) -> String {
+    unsafe { take_text(TessBaseAPIGetTsvText(self.0, page as c_int)) }.unwrap_or_default()
+  }
+
+  /// Iterate over the recognised elements of a level, once the image is recognised
+  pub fn iter(&mut self, level: Level) -> ResultIterator<'_> {
+    ResultIterator {
+      iterator: unsafe { TessBaseAPIGetIterator(self.0) },
+      level,
+      started: false,
+      api: PhantomData,
+    }
+  }
+}
+
+pub struct ResultIterator<'a> {
+  iterator: *mut TessResultIterator,
+  level: Level,
+  started: bool,
+  api: PhantomData<&'a mut TessBaseApi>,
+}
+
+impl<'a> Drop for ResultIterator<'a> {
+  fn drop(&mut self) {
+    if !self.iterator.is_null() {
+      unsafe { TessResultIteratorDelete(self.iterator) }
+    }
+  }
+}
+
+impl<'a> ResultIterator<'a> {
+  /// Move to the next element, returning false at the end of the page
+  pub fn advance(&mut self) -> bool {
+    if self.iterator.is_null() {
+      return false;
+    }
+    if !self.started {
+      self.started = true;
+      return true;
+    }
+    unsafe { TessResultIteratorNext(self.iterator, self.level) != 0 }
+  }
+
+  /// Whether the current element starts a new element of the given level
+  pub fn is_at_beginning_of(&self, level: Level) -> bool {
+    unsafe {
+      let page_iterator = TessResultIteratorGetPageIteratorConst(self.iterator);
+      TessPageIteratorIsAtBeginningOf(page_iterator, level) != 0
+    }
+  }
+
+  /// The text, bounding box and baseline accessors read the element of the given level
+  /// containing the current position.
+  pub fn text(&self, level: Level) -> String {
+    unsafe { take_text(TessResultIteratorGetUTF8Text(self.iterator, level)) }.unwrap_or_default()
+  }
+
+  pub fn confidence(&self, level: Level) -> f32 {
+    unsafe { TessResultIteratorConfidence(self.iterator, level) }
+  }
+
+  pub fn bounding_box(&self, level: Level) -> Option<Rectangle> {
+    let (mut left, mut top, mut right, mut bottom) = (0, 0, 0, 0);
+    let found = unsafe {
+      let page_iterator = TessResultIteratorGetPageIteratorConst(self.iterator);
+      TessPageIteratorBoundingBox(
+        page_iterator,
+        level,
+        &mut left,
+        &mut top,
+        &mut right,
+        &mut bottom,
+      )
+    };
+
+    if found == 0 {
+      return None;
+    }
+    Some(Rectangle {
+      left: left.max(0) as u32,
+      top: top.max(0) as u32,
+      width: (right - left).max(0) as u32,
+      height: (bottom - top).max(0) as u32,
+    })
+  }
+
+  pub fn baseline(&self, level: Level) -> Option<Baseline> {
+    let (mut x1, mut y1, mut x2, mut y2) = (0, 0, 0, 0);
+    let found = unsafe {
+      let page_iterator = TessResultIteratorGetPageIteratorConst(self.iterator);
+      TessPageIteratorBaseline(page_iterator, level, &mut x1, &mut y1, &mut x2, &mut y2)
+    };
+
+    if found == 0 {
+      return None;
+    }
+    Some(Baseline {
+      start: Point {
+        x: x1.max(0) as u32,
+        y: y1.max(0) as u32,
+      },
+      end: Point {
+        x: x2.max(0) as u32,
+        y: y2.max(0) as u32,
+      },
+    })
+  }
+}
@@ -0,0 +1,202 @@
+use super::detection::TextDetector;
+use super::tess_base_api::{TessBaseApi, BLOCK, PARAGRAPH, TEXT_LINE, WORD};
+use super::{FrameBuffer, OcrEngine, OcrSettings, Recognition};
+use crate::bidi::TextDirection;
+use crate::layout::{parse_tsv, Block, Line, Paragraph, TextLine, Word};
+use mcai_worker_sdk::MessageError;
+
+/// Initialize Tesseract, ensuring that the traineddata file of each language is loaded
+fn initialize(settings: &OcrSettings) -> Result<TessBaseApi, MessageError> {
+  let api = TessBaseApi::new(
+    settings.tessdata_path.as_deref(),
+    &settings.language,
+    settings.engine_mode,
+    &settings.variables,
+  )?;
+  let loaded_languages = api.loaded_languages();
+
+  let missing_languages: Vec<&str> = settings
+    .language
+    .split('+')
+    .filter(|language| !loaded_languages.iter().any(|loaded| loaded == language))
+    .collect();
+  if !missing_languages.is_empty() {
+    return Err(MessageError::ParameterValueError(format!(
+      "Missing traineddata for the languages: {}",
+      missing_languages.join(", ")
+    )));
+  }
+  Ok(api)
+}
+
+/// Ensure that the traineddata file of each language is available, before processing the frames
+pub fn check_languages(settings: &OcrSettings) -> Result<(), MessageError> {
+  initialize(settings).map(|_| ())
+}
+
+/// Tesseract instance initialized once for the job, then fed with each frame
+#[derive(Debug)]
+pub struct TesseractEngine {
+  api: TessBaseApi,
+  /// Detection of the text areas, recognised one by one instead of the whole frame
+  detector: Option<TextDetector>,
+}
+
+impl TesseractEngine {
+  pub fn new(settings: &OcrSettings) -> Result<Self, MessageError> {
+    let mut api = initialize(settings)?;
+    if let Some(page_segmentation_mode) = settings.page_segmentation_mode {
+      api.set_page_segmentation_mode(page_segmentation_mode);
+    }
+    let detector = settings
+      .text_detection
+      .as_ref()
+      .map(|text_detection| TextDetector::new(text_detection, settings.device))
+      .transpose()?;
+    Ok(TesseractEngine { api, detector })
+  }
+}
+
+impl OcrEngine for TesseractEngine {
+  fn recognise(
+    &mut self,
+    buffer: &FrameBuffer,
+    settings: &OcrSettings,
+    page: i32,
+  ) -> Result<Recognition, MessageError> {
+    let regions = match &mut self.detector {
+      Some(detector) => Some(detector.detect(buffer)?),
+      None => None,
+    };
+
+    let api = &mut self.api;
+    api.set_image(buffer);
+    match regions {
+      Some(regions) => {
+        let mut recognition = Recognition::default();
+        for region in &regions {
+          api.set_rectangle(&region.area);
+          api.recognize()?;
+          recognition.append(read_recognition(api, settings, page));
+        }
+        Ok(recognition)
+      }
+      None => {
+        api.recognize()?;
+        Ok(read_recognition(api, settings, page))
+      }
+    }
+  }
+}
+
+/// Text and layout of the recognised image
+fn read_recognition(api: &mut TessBaseApi, settings: &OcrSettings, page: i32) -> Recognition {
+  let text = api.get_text();
+  let hocr = if settings.hocr {
+    Some(api.get_hocr_text(page))
+  } else {
+    None
+  };
+
+  let words = parse_tsv(&api.get_tsv_text(page));
+  let lines = read_lines(api);
+  let blocks = if settings.layout {
+    read_blocks(api)
+  } else {
+    vec![]
+  };
+
+  Recognition {
+    text,
+    hocr,
+    words,
+    lines,
+    blocks,
+  }
+}
+
+fn read_lines(api: &mut TessBaseApi) -> Vec<Line> {
+  let mut lines = vec![];
+  let mut iterator = api.iter(TEXT_LINE);
+
+  while iterator.advance() {
+    let text = iterator.text(TEXT_LINE);
+    if text.trim().is_empty() {
+      continue;
+    }
+
+    if let Some(bounding_box) = iterator.bounding_box(TEXT_LINE) {
+      lines.push(Line {
+        text: text.trim().to_string(),
+        bounding_box,
+        baseline: iterator.baseline(TEXT_LINE),
+        confidence: iterator.confidence(TEXT_LINE),
+        direction: TextDirection::of(&text),
+      });
+    }
+  }
+  lines
+}
+
+/// Last element of the list, starting a new one first when required
+fn start_or_continue<T>(items: &mut Vec<T>, start: bool, create: impl FnOnce() -> T) -> &mut T {
+  if start || items.is_empty() {
+    items.push(create());
+  }
+  let last = items.len() - 1;
+  &mut items[last]
+}
+
+/// Walk the words in reading order, building the block, paragraph and line hierarchy
+fn read_blocks(api: &mut TessBaseApi) -> Vec<Block> {
+  let mut blocks: Vec<Block> = vec![];
+  let mut iterator = api.iter(WORD);
+
+  while iterator.advance() {
+    let word_box = match iterator.bounding_box(WORD) {
+      Some(bounding_box) => bounding_box,
+      None => continue,
+    };
+
+    let new_block = iterator.is_at_beginning_of(BLOCK);
+    let new_paragraph = new_block || iterator.is_at_beginning_of(PARAGRAPH);
+    let new_line = new_paragraph || iterator.is_at_beginning_of(TEXT_LINE);
+
+    let block_number = blocks.len() as u32 + if new_block { 1 } else { 0 };
+    let block = start_or_continue(&mut blocks, new_block, || Block {
+      bounding_box: iterator.bounding_box(BLOCK).unwrap_or(word_box),
+      paragraphs: vec![],
+    });
+
+    let paragraph_number = block.paragraphs.len() as u32 + if new_paragraph { 1 } else { 0 };
+    let paragraph = start_or_continue(&mut block.paragraphs, new_paragraph, || Paragraph {
+      bounding_box: iterator.bounding_box(PARAGRAPH).unwrap_or(word_box),
+      lines: vec![],
+    });
+
+    let line_number = paragraph.lines.len() as u32 + if new_line { 1 } else { 0 };
+    let text_line = start_or_continue(&mut paragraph.lines, new_line, || TextLine {
+      line: {
+        let text = iterator.text(TEXT_LINE);
+        Line {
+          text: text.trim().to_string(),
+          bounding_box: iterator.bounding_box(TEXT_LINE).unwrap_or(word_box),
+          baseline: iterator.baseline(TEXT_LINE),
+          confidence: iterator.confidence(TEXT_LINE),
+          direction: TextDirection::of(&text),
+        }
+      },
+      words: vec![],
+    });
+
+    text_line.words.push(Word {
+      text: iterator.text(WORD).trim().to_string(),
+      bounding_box: word_box,
+      confidence: iterator.confidence(WORD),
+      block: block_number.max(1),
+      paragraph: paragraph_number.max(1),
+      line: line_number.max(1),
+    });
+  }
+  blocks
+}
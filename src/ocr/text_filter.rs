@@ -0,0 +1,51 @@
+use mcai_worker_sdk::MessageError;
+use regex::Regex;
+
+/// Patterns selecting the recognised lines to keep
+#[derive(Clone, Debug)]
+pub struct TextFilter {
+  include: Option<Regex>,
+  exclude: Option<Regex>,
+}
+
+fn compile(name: &str, pattern: &Option<String>) -> Result<Option<Regex>, MessageError> {
+  pattern
+    .as_ref()
+    .map(|pattern| {
+      Regex::new(pattern).map_err(|error| {
+        MessageError::ParameterValueError(format!("Invalid {} {}: {}", name, pattern, error))
+      })
+    })
+    .transpose()
+}
+
+impl TextFilter {
+  /// Filter of the include and exclude patterns, None when both are unset
+  pub fn new(
+    include: &Option<String>,
+    exclude: &Option<String>,
+  ) -> Result<Option<Self>, MessageError> {
+    let filter = TextFilter {
+      include: compile("include_pattern", include)?,
+      exclude: compile("exclude_pattern", exclude)?,
+    };
+    if filter.include.is_none() && filter.exclude.is_none() {
+      return Ok(None);
+    }
+    Ok(Some(filter))
+  }
+
+  /// Whether the line matches the include pattern, and not the exclude pattern
+  pub fn keeps(&self, text: &str) -> bool {
+    self
+      .include
+      .as_ref()
+      .map(|include| include.is_match(text))
+      .unwrap_or(true)
+      && !self
+        .exclude
+        .as_ref()
+        .map(|exclude| exclude.is_match(text))
+        .unwrap_or(false)
+  }
+}
@@ -0,0 +1,91 @@
+use super::{FrameBuffer, OwnedFrame};
+
+/// Luminance of the diffuse white of the high dynamic range sources, in nits
+const REFERENCE_WHITE: f64 = 203.0;
+/// Nominal peak luminance of the HLG displays, in nits
+const HLG_PEAK: f64 = 1000.0;
+
+/// Transfer function of the high dynamic range sources
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transfer {
+  /// Perceptual quantizer of SMPTE ST 2084
+  Pq,
+  /// Hybrid log-gamma of ARIB STD-B67
+  Hlg,
+}
+
+impl Transfer {
+  /// Display luminance of the signal, in nits
+  fn luminance(&self, signal: f64) -> f64 {
+    match self {
+      Transfer::Pq => {
+        let (m1, m2) = (2610.0 / 16384.0, 2523.0 / 4096.0 * 128.0);
+        let (c1, c2, c3) = (
+          3424.0 / 4096.0,
+          2413.0 / 4096.0 * 32.0,
+          2392.0 / 4096.0 * 32.0,
+        );
+        let power = signal.powf(1.0 / m2);
+        ((power - c1).max(0.0) / (c2 - c3 * power)).powf(1.0 / m1) * 10000.0
+      }
+      Transfer::Hlg => {
+        let a: f64 = 0.17883277;
+        let b = 1.0 - 4.0 * a;
+        let c = 0.5 - a * (4.0 * a).ln();
+        let scene = if signal <= 0.5 {
+          signal * signal / 3.0
+        } else {
+          (((signal - c) / a).exp() + b) / 12.0
+        };
+        // system gamma of the nominal display
+        scene.powf(1.2) * HLG_PEAK
+      }
+    }
+  }
+}
+
+/// Conversion of the 16 bits components of the high dynamic range frames into standard dynamic
+/// range 8 bits components, the highlights being compressed up to the peak luminance
+#[derive(Debug)]
+pub struct ToneMapping {
+  /// 8 bits component of the 10 most significant bits of the components
+  table: Vec<u8>,
+}
+
+impl ToneMapping {
+  pub fn new(transfer: Transfer, peak_luminance: f64) -> Self {
+    let peak = (peak_luminance / REFERENCE_WHITE).max(1.0);
+    let table = (0..1024)
+      .map(|value| {
+        let luminance = transfer.luminance(value as f64 / 1023.0) / REFERENCE_WHITE;
+        // extended Reinhard, the peak luminance being mapped to the white
+        let mapped = luminance * (1.0 + luminance / (peak * peak)) / (1.0 + luminance);
+        (mapped.clamp(0.0, 1.0).powf(1.0 / 2.4) * 255.0).round() as u8
+      })
+      .collect();
+
+    ToneMapping { table }
+  }
+
+  /// Frame with 8 bits components, from the frame with 16 bits little endian components
+  pub fn apply(&self, buffer: &FrameBuffer) -> OwnedFrame {
+    let line_size = (buffer.width * buffer.bytes_per_pixel) as usize;
+    let mut data = Vec::with_capacity(line_size / 2 * buffer.height as usize);
+    for y in 0..buffer.height as usize {
+      let offset = y * buffer.bytes_per_line as usize;
+      data.extend(
+        buffer.data[offset..offset + line_size]
+          .chunks_exact(2)
+          .map(|component| {
+            self.table[(u16::from_le_bytes([component[0], component[1]]) >> 6) as usize]
+          }),
+      );
+    }
+    OwnedFrame::new(
+      data,
+      buffer.width,
+      buffer.height,
+      buffer.bytes_per_pixel / 2,
+    )
+  }
+}
@@ -0,0 +1,42 @@
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use std::env;
+use std::fs;
+use std::process;
+
+/// Path of a list file, or the inline items of the list
+#[derive(Clone, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum UserList {
+  File(String),
+  Items(Vec<String>),
+}
+
+impl UserList {
+  /// Path of the list file, writing the inline items in a temporary file
+  pub fn to_file(&self, name: &str) -> Result<String, MessageError> {
+    match self {
+      UserList::File(path) => Ok(path.clone()),
+      UserList::Items(items) => {
+        let path = env::temp_dir().join(format!("{}-{}.txt", process::id(), name));
+        let mut content = items.join("\n");
+        content.push('\n');
+        fs::write(&path, content).map_err(|error| {
+          MessageError::RuntimeError(format!("Unable to write {}: {}", path.display(), error))
+        })?;
+        Ok(path.to_string_lossy().to_string())
+      }
+    }
+  }
+
+  /// Items of the list, reading the list file
+  pub fn items(&self) -> Result<Vec<String>, MessageError> {
+    match self {
+      UserList::File(path) => fs::read_to_string(path)
+        .map(|content| content.lines().map(str::to_string).collect())
+        .map_err(|error| {
+          MessageError::ParameterValueError(format!("Unable to read {}: {}", path, error))
+        }),
+      UserList::Items(items) => Ok(items.clone()),
+    }
+  }
+}
@@ -0,0 +1,200 @@
+use super::{escape_xml, OutputContext};
+use crate::built_info;
+use crate::layout::Word;
+use crate::region::Rectangle;
+use crate::RecognisedText;
+use std::io::{Result, Write};
+
+struct TextLine<'a> {
+  bounding_box: Rectangle,
+  words: Vec<&'a Word>,
+}
+
+struct TextBlock<'a> {
+  bounding_box: Rectangle,
+  lines: Vec<TextLine<'a>>,
+}
+
+/// Group the words, given in reading order, into blocks of lines
+fn group_words(words: &[Word]) -> Vec<TextBlock<'_>> {
+  let mut blocks: Vec<TextBlock> = vec![];
+  let mut previous: Option<&Word> = None;
+
+  for word in words {
+    let (same_block, same_line) = match previous {
+      Some(previous) => {
+        let same_block = previous.block == word.block;
+        (
+          same_block,
+          same_block && previous.paragraph == word.paragraph && previous.line == word.line,
+        )
+      }
+      None => (false, false),
+    };
+
+    match blocks.last_mut() {
+      Some(block) if same_block => {
+        block.bounding_box = block.bounding_box.union(&word.bounding_box);
+
+        match block.lines.last_mut() {
+          Some(line) if same_line => {
+            line.bounding_box = line.bounding_box.union(&word.bounding_box);
+            line.words.push(word);
+          }
+          _ => block.lines.push(TextLine {
+            bounding_box: word.bounding_box,
+            words: vec![word],
+          }),
+        }
+      }
+      _ => blocks.push(TextBlock {
+        bounding_box: word.bounding_box,
+        lines: vec![TextLine {
+          bounding_box: word.bounding_box,
+          words: vec![word],
+        }],
+      }),
+    }
+    previous = Some(word);
+  }
+  blocks
+}
+
+fn position(bounding_box: &Rectangle) -> String {
+  format!(
+    r#"HPOS="{}" VPOS="{}" WIDTH="{}" HEIGHT="{}""#,
+    bounding_box.left, bounding_box.top, bounding_box.width, bounding_box.height
+  )
+}
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+  writeln!(
+    writer,
+    r#"<alto xmlns="http://www.loc.gov/standards/alto/ns-v4#" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://www.loc.gov/standards/alto/ns-v4# http://www.loc.gov/alto/v4/alto-4-2.xsd">"#
+  )?;
+  writeln!(writer, "  <Description>")?;
+  writeln!(writer, "    <MeasurementUnit>pixel</MeasurementUnit>")?;
+  writeln!(writer, "    <sourceImageInformation>")?;
+  writeln!(
+    writer,
+    "      <fileName>{}</fileName>",
+    escape_xml(&context.source_path)
+  )?;
+  writeln!(writer, "    </sourceImageInformation>")?;
+  writeln!(writer, r#"    <OCRProcessing ID="OCR_0">"#)?;
+  writeln!(writer, "      <ocrProcessingStep>")?;
+  writeln!(writer, "        <processingSoftware>")?;
+  writeln!(
+    writer,
+    "          <softwareName>{}</softwareName>",
+    built_info::PKG_NAME
+  )?;
+  writeln!(
+    writer,
+    "          <softwareVersion>{}</softwareVersion>",
+    built_info::PKG_VERSION
+  )?;
+  writeln!(writer, "        </processingSoftware>")?;
+  writeln!(writer, "      </ocrProcessingStep>")?;
+  writeln!(writer, "    </OCRProcessing>")?;
+  writeln!(writer, "  </Description>")?;
+  writeln!(writer, "  <Layout>")?;
+
+  // One page per processed frame, identified by its number, the words being located in the source frame
+  for result in results {
+    let page_id = format!("page_{}", result.frame);
+    writeln!(
+      writer,
+      r#"    <Page ID="{}" PHYSICAL_IMG_NR="{}" WIDTH="{}" HEIGHT="{}">"#,
+      page_id, result.frame, context.frame_width, context.frame_height
+    )?;
+
+    let blocks = group_words(&result.words);
+    if let Some(first_block) = blocks.first() {
+      let print_space = blocks.iter().fold(first_block.bounding_box, |area, block| {
+        area.union(&block.bounding_box)
+      });
+      writeln!(writer, "      <PrintSpace {}>", position(&print_space))?;
+
+      for (block_index, block) in blocks.iter().enumerate() {
+        let block_id = format!("{}_block_{}", page_id, block_index + 1);
+        writeln!(
+          writer,
+          r#"        <TextBlock ID="{}" {}>"#,
+          block_id,
+          position(&block.bounding_box)
+        )?;
+
+        for (line_index, line) in block.lines.iter().enumerate() {
+          let line_id = format!("{}_line_{}", block_id, line_index + 1);
+          writeln!(
+            writer,
+            r#"          <TextLine ID="{}" {}>"#,
+            line_id,
+            position(&line.bounding_box)
+          )?;
+
+          for (word_index, word) in line.words.iter().enumerate() {
+            if word_index > 0 {
+              writeln!(writer, "            <SP/>")?;
+            }
+            writeln!(
+              writer,
+              r#"            <String ID="{}_string_{}" CONTENT="{}" {} WC="{:.2}"/>"#,
+              line_id,
+              word_index + 1,
+              escape_xml(&word.text),
+              position(&word.bounding_box),
+              word.confidence.max(0.0) / 100.0
+            )?;
+          }
+          writeln!(writer, "          </TextLine>")?;
+        }
+        writeln!(writer, "        </TextBlock>")?;
+      }
+      writeln!(writer, "      </PrintSpace>")?;
+    }
+    writeln!(writer, "    </Page>")?;
+  }
+
+  writeln!(writer, "  </Layout>")?;
+  writeln!(writer, "</alto>")?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn pages_of_the_frames() {
+    // the pages of a multipage TIFF scan all have a null PTS
+    let results: Vec<RecognisedText> = (0..2)
+      .map(|frame| RecognisedText {
+        frame,
+        page: Some(frame),
+        ..Default::default()
+      })
+      .collect();
+    let context = OutputContext {
+      frame_width: 2480,
+      frame_height: 3508,
+      ..Default::default()
+    };
+    let mut document = vec![];
+    write(&mut document, &results, &context).unwrap();
+    let document = String::from_utf8(document).unwrap();
+
+    assert!(
+      document.contains(r#"<Page ID="page_0" PHYSICAL_IMG_NR="0" WIDTH="2480" HEIGHT="3508">"#)
+    );
+    assert!(
+      document.contains(r#"<Page ID="page_1" PHYSICAL_IMG_NR="1" WIDTH="2480" HEIGHT="3508">"#)
+    );
+  }
+}
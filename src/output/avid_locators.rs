@@ -0,0 +1,101 @@
+use super::{timecode_frame_rate, OutputContext};
+use crate::text_event::group_text_events;
+use crate::timecode::Timecode;
+use crate::RecognisedText;
+use mcai_worker_sdk::JsonSchema;
+use std::io::{Result, Write};
+
+/// Colors available for the Avid Media Composer locators
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LocatorColor {
+  White,
+  Red,
+  Green,
+  Blue,
+  Cyan,
+  Magenta,
+  Yellow,
+  Black,
+}
+
+impl Default for LocatorColor {
+  fn default() -> Self {
+    LocatorColor::Yellow
+  }
+}
+
+impl LocatorColor {
+  fn name(self) -> &'static str {
+    match self {
+      LocatorColor::White => "white",
+      LocatorColor::Red => "red",
+      LocatorColor::Green => "green",
+      LocatorColor::Blue => "blue",
+      LocatorColor::Cyan => "cyan",
+      LocatorColor::Magenta => "magenta",
+      LocatorColor::Yellow => "yellow",
+      LocatorColor::Black => "black",
+    }
+  }
+}
+
+/// Locator fields are tab separated, on a single line
+fn sanitize(text: &str) -> String {
+  text.replace(&['\t', '\r', '\n'][..], " ")
+}
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  let frame_rate = timecode_frame_rate(context);
+
+  for event in group_text_events(results, context) {
+    writeln!(
+      writer,
+      "OCR\t{}\t{}\t{}\t{}",
+      Timecode::from_milliseconds(event.start_ms, frame_rate),
+      sanitize(&context.locator_track),
+      context.locator_color.name(),
+      sanitize(&event.single_line())
+    )?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn result(frame: u32, text: &str) -> RecognisedText {
+    RecognisedText {
+      frame,
+      pts: frame as u64 * 40,
+      timestamp_ms: frame as u64 * 40,
+      duration: 40,
+      text: text.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn tab_separated_locators() {
+    let context = OutputContext {
+      frame_rate: 25.0,
+      locator_track: "V1".to_string(),
+      locator_color: LocatorColor::Cyan,
+      ..Default::default()
+    };
+    let results = [result(50, "Tom\tJerry\nEpisode 1"), result(75, "NEWS")];
+    let mut locators = vec![];
+    write(&mut locators, &results, &context).unwrap();
+
+    assert_eq!(
+      String::from_utf8(locators).unwrap(),
+      "OCR\t00:00:02:00\tV1\tcyan\tTom Jerry / Episode 1\n\
+       OCR\t00:00:03:00\tV1\tcyan\tNEWS\n"
+    );
+  }
+}
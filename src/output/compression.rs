@@ -0,0 +1,94 @@
+use flate2::write::GzEncoder;
+use mcai_worker_sdk::JsonSchema;
+use std::io::{Result, Write};
+
+/// Compression applied to the destination file
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+  None,
+  Gzip,
+  Zstd,
+}
+
+impl Default for Compression {
+  fn default() -> Self {
+    Compression::None
+  }
+}
+
+impl Compression {
+  fn extension(self) -> Option<&'static str> {
+    match self {
+      Compression::None => None,
+      Compression::Gzip => Some(".gz"),
+      Compression::Zstd => Some(".zst"),
+    }
+  }
+
+  /// Destination path with the extension of the compressed files
+  pub fn with_extension(self, path: String) -> String {
+    match self.extension() {
+      Some(extension) if !path.ends_with(extension) => format!("{}{}", path, extension),
+      _ => path,
+    }
+  }
+
+  pub fn writer<W: Write>(self, writer: W) -> Result<CompressedWriter<W>> {
+    Ok(match self {
+      Compression::None => CompressedWriter::Plain(writer),
+      Compression::Gzip => {
+        CompressedWriter::Gzip(GzEncoder::new(writer, flate2::Compression::default()))
+      }
+      Compression::Zstd => CompressedWriter::Zstd(zstd::Encoder::new(writer, 0)?),
+    })
+  }
+}
+
+/// Writer compressing the data, to finish explicitly to write the end of the stream
+pub enum CompressedWriter<W: Write> {
+  Plain(W),
+  Gzip(GzEncoder<W>),
+  Zstd(zstd::Encoder<W>),
+}
+
+impl<W: Write> CompressedWriter<W> {
+  pub fn finish(self) -> Result<W> {
+    match self {
+      CompressedWriter::Plain(writer) => Ok(writer),
+      CompressedWriter::Gzip(encoder) => encoder.finish(),
+      CompressedWriter::Zstd(encoder) => encoder.finish(),
+    }
+  }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+  fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+    match self {
+      CompressedWriter::Plain(writer) => writer.write(buffer),
+      CompressedWriter::Gzip(encoder) => encoder.write(buffer),
+      CompressedWriter::Zstd(encoder) => encoder.write(buffer),
+    }
+  }
+
+  fn flush(&mut self) -> Result<()> {
+    match self {
+      CompressedWriter::Plain(writer) => writer.flush(),
+      CompressedWriter::Gzip(encoder) => encoder.flush(),
+      CompressedWriter::Zstd(encoder) => encoder.flush(),
+    }
+  }
+}
+
+impl<W: Write> std::fmt::Debug for CompressedWriter<W> {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    let compression = match self {
+      CompressedWriter::Plain(_) => Compression::None,
+      CompressedWriter::Gzip(_) => Compression::Gzip,
+      CompressedWriter::Zstd(_) => Compression::Zstd,
+    };
+    f.debug_tuple("CompressedWriter")
+      .field(&compression)
+      .finish()
+  }
+}
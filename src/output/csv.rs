@@ -0,0 +1,99 @@
+use super::OutputContext;
+use crate::timecode::format_milliseconds;
+use crate::RecognisedText;
+use std::io::{Result, Write};
+
+/// Quote the field when required by RFC 4180
+pub(super) fn escape(field: &str) -> String {
+  if field.contains(&[',', '"', '\n', '\r'][..]) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_string()
+  }
+}
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  let region = context
+    .region
+    .map(|region| {
+      format!(
+        "{}x{}+{}+{}",
+        region.width, region.height, region.left, region.top
+      )
+    })
+    .unwrap_or_default();
+
+  writeln!(writer, "frame,pts,timecode,roi,confidence,text")?;
+  for result in results {
+    let confidence = result
+      .confidence
+      .map(|confidence| format!("{:.2}", confidence))
+      .unwrap_or_default();
+
+    writeln!(
+      writer,
+      "{},{},{},{},{},{}",
+      result.frame,
+      result.pts,
+      format_milliseconds(context.time_base.to_milliseconds(result.pts), '.'),
+      region,
+      confidence,
+      escape(result.text.trim())
+    )?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::region::Rectangle;
+
+  #[test]
+  fn escaped_fields() {
+    assert_eq!(escape("NEWS"), "NEWS");
+    assert_eq!(escape("Tom, Jerry"), "\"Tom, Jerry\"");
+    assert_eq!(escape("Tom \"Jerry\""), "\"Tom \"\"Jerry\"\"\"");
+    assert_eq!(escape("Tom\nJerry"), "\"Tom\nJerry\"");
+  }
+
+  #[test]
+  fn rows_of_the_frames() {
+    let context = OutputContext {
+      region: Some(Rectangle {
+        left: 0,
+        top: 810,
+        width: 1920,
+        height: 270,
+      }),
+      ..Default::default()
+    };
+    let results = [
+      RecognisedText {
+        frame: 25,
+        pts: 1000,
+        text: " Tom, Jerry\n".to_string(),
+        confidence: Some(91.256),
+        ..Default::default()
+      },
+      RecognisedText {
+        frame: 50,
+        pts: 2000,
+        ..Default::default()
+      },
+    ];
+    let mut table = vec![];
+    write(&mut table, &results, &context).unwrap();
+
+    assert_eq!(
+      String::from_utf8(table).unwrap(),
+      "frame,pts,timecode,roi,confidence,text\n\
+       25,1000,00:00:01.000,1920x270+0+810,91.26,\"Tom, Jerry\"\n\
+       50,2000,00:00:02.000,1920x270+0+810,,\n"
+    );
+  }
+}
@@ -0,0 +1,314 @@
+use super::OutputContext;
+use crate::text_event::group_text_events;
+use crate::timecode::{civil_date, Timecode};
+use crate::RecognisedText;
+use std::convert::TryFrom;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const GSI_BLOCK_SIZE: usize = 1024;
+const TTI_TEXT_FIELD_SIZE: usize = 112;
+
+const TEXT_LINE_BREAK: u8 = 0x8a;
+const TEXT_UNUSED_SPACE: u8 = 0x8f;
+const LAST_EXTENSION_BLOCK: u8 = 0xff;
+const JUSTIFICATION_CENTERED: u8 = 0x02;
+const MAX_DISPLAYABLE_ROWS: u32 = 23;
+/// Largest number of TTI blocks in the five digits of the GSI block
+const MAX_TTI_BLOCKS: usize = 99_999;
+
+/// Append an ASCII field, space padded or truncated to its size
+fn push_field(block: &mut Vec<u8>, value: &str, size: usize) {
+  let mut field: Vec<u8> = value.bytes().filter(u8::is_ascii).take(size).collect();
+  field.resize(size, b' ');
+  block.extend(field);
+}
+
+/// Date formatted as YYMMDD
+fn today() -> String {
+  let days = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_secs() / 86_400)
+    .unwrap_or(0) as i64;
+  let (year, month, day) = civil_date(days);
+
+  format!("{:02}{:02}{:02}", year % 100, month, day)
+}
+
+/// EBU Tech 3264 language codes of the Tesseract languages
+fn language_code(language: &str) -> &'static str {
+  match language.split('+').next().unwrap_or_default() {
+    "deu" => "08",
+    "eng" => "09",
+    "spa" => "0A",
+    "fra" => "0F",
+    "ita" => "15",
+    "nld" => "1D",
+    "por" => "21",
+    _ => "00",
+  }
+}
+
+/// Encode text with the ISO 6937 Latin character code table,
+/// using the non-spacing diacritical marks for accented letters.
+fn encode_latin(text: &str) -> Vec<u8> {
+  const DIACRITICS: [(u8, &str, &str); 7] = [
+    (0xc1, "àèìòùÀÈÌÒÙ", "aeiouAEIOU"),
+    (0xc2, "áéíóúýÁÉÍÓÚÝ", "aeiouyAEIOUY"),
+    (0xc3, "âêîôûÂÊÎÔÛ", "aeiouAEIOU"),
+    (0xc4, "ãñõÃÑÕ", "anoANO"),
+    (0xc8, "äëïöüÿÄËÏÖÜŸ", "aeiouyAEIOUY"),
+    (0xca, "åÅ", "aA"),
+    (0xcb, "çÇ", "cC"),
+  ];
+
+  let mut bytes = vec![];
+  for character in text.chars() {
+    match character {
+      '$' => bytes.push(0xa4),
+      'Æ' => bytes.push(0xe1),
+      'Ø' => bytes.push(0xe9),
+      'Œ' => bytes.push(0xea),
+      'æ' => bytes.push(0xf1),
+      'ø' => bytes.push(0xf9),
+      'œ' => bytes.push(0xfa),
+      'ß' => bytes.push(0xfb),
+      character if character.is_ascii() && !character.is_ascii_control() => {
+        bytes.push(character as u8)
+      }
+      character => {
+        let accented = DIACRITICS.iter().find_map(|(mark, accented, base)| {
+          accented
+            .chars()
+            .position(|candidate| candidate == character)
+            .and_then(|position| base.chars().nth(position))
+            .map(|base| (*mark, base as u8))
+        });
+
+        match accented {
+          Some((mark, base)) => bytes.extend(&[mark, base]),
+          None => bytes.push(b'?'),
+        }
+      }
+    }
+  }
+  bytes
+}
+
+fn push_timecode(block: &mut Vec<u8>, timecode: Timecode) {
+  block.extend(&[
+    timecode.hours as u8,
+    timecode.minutes as u8,
+    timecode.seconds as u8,
+    timecode.frames as u8,
+  ]);
+}
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  let (disk_format, frame_rate) = if context.frame_rate > 27.0 {
+    ("STL30.01", 30)
+  } else {
+    ("STL25.01", 25)
+  };
+
+  // Vertical position of the text as teletext row, below the region of interest when available
+  let vertical_position = context
+    .region
+    .filter(|_| context.frame_height > 0)
+    .map(|region| {
+      let bottom = (region.top + region.height) as u64;
+      (1 + bottom * (MAX_DISPLAYABLE_ROWS - 1) as u64 / context.frame_height as u64) as u8
+    })
+    .unwrap_or(20);
+
+  let mut tti_blocks = vec![];
+  let cues = group_text_events(results, context);
+
+  for (subtitle_number, cue) in cues.iter().enumerate() {
+    // the subtitle numbers are stored on two bytes
+    let subtitle_number = u16::try_from(subtitle_number).map_err(|_| {
+      Error::new(
+        ErrorKind::InvalidData,
+        format!(
+          "Too many subtitles for an EBU-STL file, the limit being {}",
+          u16::MAX as usize + 1
+        ),
+      )
+    })?;
+    let lines: Vec<Vec<u8>> = cue.lines().map(encode_latin).collect();
+    let text = lines.join(&TEXT_LINE_BREAK);
+
+    let chunks: Vec<&[u8]> = text.chunks(TTI_TEXT_FIELD_SIZE).collect();
+    for (extension, chunk) in chunks.iter().enumerate() {
+      let mut block = Vec::with_capacity(128);
+      block.push(0);
+      block.extend(&subtitle_number.to_le_bytes());
+      block.push(if extension + 1 == chunks.len() {
+        LAST_EXTENSION_BLOCK
+      } else {
+        extension as u8
+      });
+      block.push(0);
+      push_timecode(
+        &mut block,
+        Timecode::from_milliseconds(cue.start_ms, frame_rate),
+      );
+      push_timecode(
+        &mut block,
+        Timecode::from_milliseconds(cue.end_ms, frame_rate),
+      );
+      block.push(vertical_position);
+      block.push(JUSTIFICATION_CENTERED);
+      block.push(0);
+      block.extend(*chunk);
+      block.resize(128, TEXT_UNUSED_SPACE);
+      tti_blocks.push(block);
+    }
+  }
+
+  if tti_blocks.len() > MAX_TTI_BLOCKS {
+    return Err(Error::new(
+      ErrorKind::InvalidData,
+      format!(
+        "Too many TTI blocks for an EBU-STL file, the limit being {}",
+        MAX_TTI_BLOCKS
+      ),
+    ));
+  }
+
+  let first_cue_timecode = cues
+    .first()
+    .map(|cue| Timecode::from_milliseconds(cue.start_ms, frame_rate))
+    .map(|timecode| {
+      format!(
+        "{:02}{:02}{:02}{:02}",
+        timecode.hours, timecode.minutes, timecode.seconds, timecode.frames
+      )
+    })
+    .unwrap_or_else(|| "00000000".to_string());
+
+  let programme_title = std::path::Path::new(&context.source_path)
+    .file_stem()
+    .map(|stem| stem.to_string_lossy().to_string())
+    .unwrap_or_default();
+  let creation_date = today();
+
+  let mut gsi = Vec::with_capacity(GSI_BLOCK_SIZE);
+  push_field(&mut gsi, "850", 3);
+  push_field(&mut gsi, disk_format, 8);
+  push_field(&mut gsi, "1", 1);
+  push_field(&mut gsi, "00", 2);
+  push_field(&mut gsi, language_code(&context.language), 2);
+  push_field(&mut gsi, &programme_title, 32);
+  push_field(&mut gsi, "", 32 * 5);
+  push_field(&mut gsi, &format!("JOB{}", context.job_id), 16);
+  push_field(&mut gsi, &creation_date, 6);
+  push_field(&mut gsi, &creation_date, 6);
+  push_field(&mut gsi, "00", 2);
+  push_field(&mut gsi, &format!("{:05}", tti_blocks.len()), 5);
+  push_field(&mut gsi, &format!("{:05}", cues.len()), 5);
+  push_field(&mut gsi, "001", 3);
+  push_field(&mut gsi, "40", 2);
+  push_field(&mut gsi, &MAX_DISPLAYABLE_ROWS.to_string(), 2);
+  push_field(&mut gsi, "1", 1);
+  push_field(&mut gsi, "00000000", 8);
+  push_field(&mut gsi, &first_cue_timecode, 8);
+  push_field(&mut gsi, "1", 1);
+  push_field(&mut gsi, "1", 1);
+  push_field(&mut gsi, "", 3 + 32 * 3 + 75 + 576);
+
+  writer.write_all(&gsi)?;
+  for block in tti_blocks {
+    writer.write_all(&block)?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn result(frame: u32, text: &str) -> RecognisedText {
+    RecognisedText {
+      frame,
+      pts: frame as u64 * 40,
+      timestamp_ms: frame as u64 * 40,
+      duration: 40,
+      text: text.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn gsi_and_tti_blocks_layout() {
+    let context = OutputContext {
+      source_path: "/data/news.mxf".to_string(),
+      language: "fra".to_string(),
+      frame_rate: 25.0,
+      ..Default::default()
+    };
+    let results = [result(0, "Été\nBonjour"), result(25, "Au revoir")];
+    let mut file = vec![];
+    write(&mut file, &results, &context).unwrap();
+
+    assert_eq!(file.len(), GSI_BLOCK_SIZE + 2 * 128);
+    let gsi = &file[..GSI_BLOCK_SIZE];
+    assert_eq!(&gsi[0..3], b"850");
+    assert_eq!(&gsi[3..11], b"STL25.01");
+    assert_eq!(&gsi[14..16], b"0F");
+    assert_eq!(&gsi[16..48], format!("{:32}", "news").as_bytes());
+    assert_eq!(&gsi[238..243], b"00002");
+    assert_eq!(&gsi[243..248], b"00002");
+    assert_eq!(&gsi[253..255], b"23");
+    assert_eq!(&gsi[256..264], b"00000000");
+    assert_eq!(&gsi[264..272], b"00000000");
+
+    let first = &file[GSI_BLOCK_SIZE..GSI_BLOCK_SIZE + 128];
+    assert_eq!(&first[0..4], &[0, 0, 0, LAST_EXTENSION_BLOCK]);
+    assert_eq!(&first[5..9], &[0, 0, 0, 0]);
+    assert_eq!(&first[9..13], &[0, 0, 1, 0]);
+    assert_eq!(&first[13..15], &[20, JUSTIFICATION_CENTERED]);
+    assert_eq!(
+      &first[16..27],
+      &[
+        0xc2,
+        b'E',
+        b't',
+        0xc2,
+        b'e',
+        TEXT_LINE_BREAK,
+        b'B',
+        b'o',
+        b'n',
+        b'j',
+        b'o'
+      ]
+    );
+    assert!(first[30..].iter().all(|&byte| byte == TEXT_UNUSED_SPACE));
+
+    let second = &file[GSI_BLOCK_SIZE + 128..];
+    assert_eq!(&second[1..3], &[1, 0]);
+    assert_eq!(&second[5..9], &[0, 0, 1, 0]);
+    assert_eq!(&second[9..13], &[0, 0, 1, 1]);
+    assert_eq!(&second[16..25], b"Au revoir");
+  }
+
+  #[test]
+  fn long_texts_span_extension_blocks() {
+    let context = OutputContext {
+      frame_rate: 25.0,
+      ..Default::default()
+    };
+    let results = [result(0, &"A".repeat(TTI_TEXT_FIELD_SIZE + 10))];
+    let mut file = vec![];
+    write(&mut file, &results, &context).unwrap();
+
+    assert_eq!(file.len(), GSI_BLOCK_SIZE + 2 * 128);
+    assert_eq!(file[GSI_BLOCK_SIZE + 3], 0);
+    assert_eq!(file[GSI_BLOCK_SIZE + 128 + 3], LAST_EXTENSION_BLOCK);
+  }
+}
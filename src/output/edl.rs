@@ -0,0 +1,118 @@
+use super::{timecode_frame_rate, OutputContext};
+use crate::text_event::group_text_events;
+use crate::timecode::Timecode;
+use crate::RecognisedText;
+use std::io::{Result, Write};
+use std::path::Path;
+
+/// Reel name of the source, limited to the 8 characters allowed by CMX3600
+fn reel_name(source_path: &str) -> String {
+  let name: String = Path::new(source_path)
+    .file_stem()
+    .map(|stem| stem.to_string_lossy().to_string())
+    .unwrap_or_default()
+    .chars()
+    .filter(|character| character.is_ascii_alphanumeric())
+    .take(8)
+    .collect::<String>()
+    .to_uppercase();
+
+  if name.is_empty() {
+    "AX".to_string()
+  } else {
+    name
+  }
+}
+
+/// CMX3600 comments are plain ASCII lines
+fn to_ascii(text: &str) -> String {
+  text
+    .chars()
+    .map(|character| {
+      if character.is_ascii() && !character.is_ascii_control() {
+        character
+      } else {
+        '?'
+      }
+    })
+    .collect()
+}
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  let frame_rate = timecode_frame_rate(context);
+  let reel = reel_name(&context.source_path);
+
+  writeln!(writer, "TITLE: {}", to_ascii(&context.source_path))?;
+  writeln!(writer, "FCM: NON-DROP FRAME")?;
+  writeln!(writer)?;
+
+  for (index, event) in group_text_events(results, context).iter().enumerate() {
+    let start = Timecode::from_milliseconds(event.start_ms, frame_rate);
+    let end = Timecode::from_milliseconds(event.end_ms, frame_rate);
+
+    writeln!(
+      writer,
+      "{:03}  {:<8} V     C        {} {} {} {}",
+      index + 1,
+      reel,
+      start,
+      end,
+      start,
+      end
+    )?;
+    writeln!(
+      writer,
+      "* LOC: {} YELLOW  {}",
+      start,
+      to_ascii(&event.single_line())
+    )?;
+    writeln!(writer)?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn result(frame: u32, text: &str) -> RecognisedText {
+    RecognisedText {
+      frame,
+      pts: frame as u64 * 40,
+      timestamp_ms: frame as u64 * 40,
+      duration: 40,
+      text: text.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn reel_names() {
+    assert_eq!(reel_name("/media/news_2021-03.mxf"), "NEWS2021");
+    assert_eq!(reel_name("s3://bucket/a.mp4"), "A");
+    assert_eq!(reel_name("/media/é.mp4"), "AX");
+  }
+
+  #[test]
+  fn locators_of_the_text_events() {
+    let context = OutputContext {
+      source_path: "/media/news.mxf".to_string(),
+      frame_rate: 25.0,
+      ..Default::default()
+    };
+    let results = [result(50, "Café\nSport"), result(51, "")];
+    let mut list = vec![];
+    write(&mut list, &results, &context).unwrap();
+
+    assert_eq!(
+      String::from_utf8(list).unwrap(),
+      "TITLE: /media/news.mxf\nFCM: NON-DROP FRAME\n\n\
+       001  NEWS     V     C        00:00:02:00 00:00:02:01 00:00:02:00 00:00:02:01\n\
+       * LOC: 00:00:02:00 YELLOW  Caf? / Sport\n\n"
+    );
+  }
+}
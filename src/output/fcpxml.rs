@@ -0,0 +1,159 @@
+use super::{escape_xml, OutputContext};
+use crate::text_event::group_text_events;
+use crate::timecode::TimeBase;
+use crate::RecognisedText;
+use std::io::{Result, Write};
+use std::path::Path;
+
+/// Rational time in seconds, as expected by FCPXML attributes
+fn rational_time(pts: u64, time_base: &TimeBase) -> String {
+  if time_base.den == 0 {
+    return "0s".to_string();
+  }
+  format!("{}/{}s", pts as u128 * time_base.num as u128, time_base.den)
+}
+
+/// Duration of a frame, using the NTSC rationals for the 1000/1001 frame rates
+fn frame_duration(frame_rate: f64) -> String {
+  if frame_rate <= 0.0 {
+    return "1/25s".to_string();
+  }
+  let rounded = frame_rate.round();
+  if (frame_rate - rounded).abs() < 0.001 {
+    return format!("1/{}s", rounded as u32);
+  }
+  format!("1001/{}s", (frame_rate * 1.001).round() as u32 * 1000)
+}
+
+fn source_url(source_path: &str) -> String {
+  if source_path.contains("://") {
+    source_path.to_string()
+  } else {
+    format!("file://{}", source_path)
+  }
+}
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  let time_base = &context.time_base;
+  let name = Path::new(&context.source_path)
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_else(|| context.source_path.clone());
+  let duration = results
+    .last()
+    .map(|result| rational_time(result.pts + result.duration, time_base))
+    .unwrap_or_else(|| "0s".to_string());
+
+  writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+  writeln!(writer, "<!DOCTYPE fcpxml>")?;
+  writeln!(writer, r#"<fcpxml version="1.8">"#)?;
+  writeln!(writer, "  <resources>")?;
+  writeln!(
+    writer,
+    r#"    <format id="r1" frameDuration="{}" width="{}" height="{}"/>"#,
+    frame_duration(context.frame_rate),
+    context.frame_width,
+    context.frame_height
+  )?;
+  writeln!(
+    writer,
+    r#"    <asset id="r2" name="{}" src="{}" start="0s" duration="{}" hasVideo="1" format="r1"/>"#,
+    escape_xml(&name),
+    escape_xml(&source_url(&context.source_path)),
+    duration
+  )?;
+  writeln!(writer, "  </resources>")?;
+  writeln!(writer, "  <library>")?;
+  writeln!(writer, r#"    <event name="Text recognition">"#)?;
+  writeln!(writer, r#"      <project name="{}">"#, escape_xml(&name))?;
+  writeln!(
+    writer,
+    r#"        <sequence format="r1" duration="{}" tcStart="0s" tcFormat="NDF">"#,
+    duration
+  )?;
+  writeln!(writer, "          <spine>")?;
+  writeln!(
+    writer,
+    r#"            <asset-clip name="{}" ref="r2" offset="0s" start="0s" duration="{}">"#,
+    escape_xml(&name),
+    duration
+  )?;
+
+  for event in group_text_events(results, context) {
+    writeln!(
+      writer,
+      r#"              <marker start="{}" duration="{}" value="{}"/>"#,
+      rational_time(event.start_pts, time_base),
+      rational_time(event.end_pts - event.start_pts, time_base),
+      escape_xml(&event.single_line())
+    )?;
+  }
+
+  writeln!(writer, "            </asset-clip>")?;
+  writeln!(writer, "          </spine>")?;
+  writeln!(writer, "        </sequence>")?;
+  writeln!(writer, "      </project>")?;
+  writeln!(writer, "    </event>")?;
+  writeln!(writer, "  </library>")?;
+  writeln!(writer, "</fcpxml>")?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn result(frame: u32, text: &str) -> RecognisedText {
+    RecognisedText {
+      frame,
+      pts: frame as u64 * 40,
+      timestamp_ms: frame as u64 * 40,
+      duration: 40,
+      text: text.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn rational_times() {
+    assert_eq!(
+      rational_time(1001, &TimeBase { num: 1, den: 30000 }),
+      "1001/30000s"
+    );
+    assert_eq!(rational_time(10, &TimeBase { num: 0, den: 0 }), "0s");
+    assert_eq!(frame_duration(25.0), "1/25s");
+    assert_eq!(frame_duration(29.97), "1001/30000s");
+    assert_eq!(frame_duration(0.0), "1/25s");
+  }
+
+  #[test]
+  fn markers_of_the_text_events() {
+    let context = OutputContext {
+      source_path: "/media/Tom & Jerry.mov".to_string(),
+      frame_rate: 25.0,
+      frame_width: 1920,
+      frame_height: 1080,
+      ..Default::default()
+    };
+    let results = [
+      result(0, "Tom & Jerry\nEpisode 1"),
+      result(1, "Tom & Jerry\nEpisode 1"),
+      result(2, "NEWS"),
+    ];
+    let mut document = vec![];
+    write(&mut document, &results, &context).unwrap();
+    let document = String::from_utf8(document).unwrap();
+
+    assert!(document.contains(
+      r#"<asset id="r2" name="Tom &amp; Jerry.mov" src="file:///media/Tom &amp; Jerry.mov" start="0s" duration="120/1000s" hasVideo="1" format="r1"/>"#
+    ));
+    assert!(document.contains(
+      r#"<marker start="0/1000s" duration="80/1000s" value="Tom &amp; Jerry / Episode 1"/>"#
+    ));
+    assert!(document.contains(r#"<marker start="80/1000s" duration="40/1000s" value="NEWS"/>"#));
+  }
+}
@@ -0,0 +1,15 @@
+use crate::summary::JobSummary;
+use crate::RecognisedText;
+use std::io::{Result, Write};
+
+/// Results of the processed frames, followed by the summary of the job
+#[derive(Serialize)]
+pub struct Document<'a> {
+  pub results: &'a [RecognisedText],
+  pub summary: &'a JobSummary,
+}
+
+pub fn write<W: Write>(writer: &mut W, document: &Document) -> Result<()> {
+  serde_json::to_writer_pretty(writer, document)?;
+  Ok(())
+}
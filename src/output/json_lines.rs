@@ -0,0 +1,47 @@
+use super::compression::{CompressedWriter, Compression};
+use mcai_worker_sdk::MessageError;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// JSON Lines file, appended and flushed on every processed frame
+/// so that the output remains usable while the job is running.
+#[derive(Debug)]
+pub struct JsonLinesWriter {
+  path: String,
+  writer: CompressedWriter<BufWriter<File>>,
+}
+
+impl JsonLinesWriter {
+  pub fn create(path: &str, compression: Compression) -> Result<Self, MessageError> {
+    let writer = File::create(path)
+      .and_then(|file| compression.writer(BufWriter::new(file)))
+      .map_err(|error| {
+        MessageError::RuntimeError(format!("Unable to create {}: {}", path, error))
+      })?;
+
+    Ok(JsonLinesWriter {
+      path: path.to_string(),
+      writer,
+    })
+  }
+
+  pub fn write<T: serde::Serialize>(&mut self, value: &T) -> Result<(), MessageError> {
+    serde_json::to_writer(&mut self.writer, value)
+      .map_err(std::io::Error::from)
+      .and_then(|_| writeln!(self.writer))
+      .and_then(|_| self.writer.flush())
+      .map_err(|error| {
+        MessageError::RuntimeError(format!("Unable to write {}: {}", self.path, error))
+      })
+  }
+
+  /// Write the end of the compressed stream
+  pub fn finish(self) -> Result<(), MessageError> {
+    let path = self.path;
+    self
+      .writer
+      .finish()
+      .and_then(|mut writer| writer.flush())
+      .map_err(|error| MessageError::RuntimeError(format!("Unable to write {}: {}", path, error)))
+  }
+}
@@ -0,0 +1,165 @@
+mod alto;
+mod avid_locators;
+mod compression;
+mod csv;
+mod ebu_stl;
+mod edl;
+mod fcpxml;
+mod json;
+mod json_lines;
+mod premiere_markers;
+mod srt;
+mod text_events;
+mod ttml;
+mod webvtt;
+mod xml;
+
+use crate::region::Rectangle;
+use crate::sampling::Segments;
+use crate::summary::JobSummary;
+use crate::timecode::TimeBase;
+use crate::RecognisedText;
+
+pub use avid_locators::LocatorColor;
+pub use compression::Compression;
+pub use json_lines::JsonLinesWriter;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+  /// JSON document with the recognised text of each processed frame in results,
+  /// and the job summary in summary
+  Json,
+  /// SubRip subtitles, one cue per text event
+  Srt,
+  /// WebVTT subtitles, one cue per text event
+  WebVtt,
+  /// TTML subtitles following the IMSC1 text profile
+  Ttml,
+  /// EBU-STL binary subtitles (Tech 3264)
+  #[serde(rename = "ebu_stl")]
+  EbuStl,
+  /// ALTO XML, one page per processed frame with the word positions
+  Alto,
+  /// CSV table, one row per processed frame
+  Csv,
+  /// JSON Lines, one line appended per processed frame while the job is running,
+  /// the job summary being the last line
+  #[serde(rename = "json_lines")]
+  JsonLines,
+  /// JSON array of text events, merging the consecutive frames showing the same text
+  #[serde(rename = "text_events")]
+  TextEvents,
+  /// Final Cut Pro XML, with a marker on the source clip for each text event
+  Fcpxml,
+  /// CMX3600 edit decision list, with a locator for each text event
+  Edl,
+  /// Marker CSV imported by Adobe Premiere Pro, one marker per text event
+  #[serde(rename = "premiere_markers")]
+  PremiereMarkers,
+  /// Avid Media Composer locators, one per text event
+  #[serde(rename = "avid_locators")]
+  AvidLocators,
+  /// XML document mirroring the structure of the JSON output, with the job summary
+  Xml,
+}
+
+impl Default for OutputFormat {
+  fn default() -> Self {
+    OutputFormat::Json
+  }
+}
+
+impl OutputFormat {
+  /// Whether the results are written as they are produced, rather than at the end of the job
+  pub fn is_streamed(self) -> bool {
+    self == OutputFormat::JsonLines
+  }
+}
+
+/// Description of the processed stream, required by the timed output formats
+#[derive(Debug, Default)]
+pub struct OutputContext {
+  pub job_id: u64,
+  pub source_path: String,
+  pub language: String,
+  pub time_base: TimeBase,
+  pub frame_rate: f64,
+  pub frame_width: u32,
+  pub frame_height: u32,
+  pub region: Option<Rectangle>,
+  pub merge_distance: usize,
+  /// Whether the listed frames are recognised rather than the sampled ones
+  pub listed_frames: bool,
+  /// Segments of the source recognised separately
+  pub segments: Option<Segments>,
+  pub locator_track: String,
+  pub locator_color: LocatorColor,
+}
+
+pub fn to_file(
+  path: &str,
+  format: OutputFormat,
+  compression: Compression,
+  results: &[RecognisedText],
+  summary: &JobSummary,
+  context: &OutputContext,
+) -> Result<(), MessageError> {
+  // streamed formats are written while processing the frames
+  if format.is_streamed() {
+    return Ok(());
+  }
+
+  let mut writer = File::create(path)
+    .and_then(|file| compression.writer(BufWriter::new(file)))
+    .map_err(|error| MessageError::RuntimeError(format!("Unable to create {}: {}", path, error)))?;
+
+  match format {
+    OutputFormat::JsonLines => Ok(()),
+    OutputFormat::Json => json::write(&mut writer, &json::Document { results, summary }),
+    OutputFormat::Srt => srt::write(&mut writer, results, context),
+    OutputFormat::WebVtt => webvtt::write(&mut writer, results, context),
+    OutputFormat::Ttml => ttml::write(&mut writer, results, context),
+    OutputFormat::EbuStl => ebu_stl::write(&mut writer, results, context),
+    OutputFormat::Alto => alto::write(&mut writer, results, context),
+    OutputFormat::Csv => csv::write(&mut writer, results, context),
+    OutputFormat::TextEvents => text_events::write(&mut writer, results, context),
+    OutputFormat::Fcpxml => fcpxml::write(&mut writer, results, context),
+    OutputFormat::Edl => edl::write(&mut writer, results, context),
+    OutputFormat::PremiereMarkers => premiere_markers::write(&mut writer, results, context),
+    OutputFormat::AvidLocators => avid_locators::write(&mut writer, results, context),
+    OutputFormat::Xml => xml::write(&mut writer, &json::Document { results, summary }),
+  }
+  .and_then(|_| writer.finish())
+  .and_then(|mut writer| writer.flush())
+  .map_err(|error| MessageError::RuntimeError(format!("Unable to write {}: {}", path, error)))
+}
+
+/// Integer frame rate used to count the frames of the non-drop timecodes
+pub fn timecode_frame_rate(context: &OutputContext) -> u32 {
+  if context.frame_rate > 0.0 {
+    context.frame_rate.round() as u32
+  } else {
+    25
+  }
+}
+
+/// Express a pixel distance as a percentage of the frame dimension
+fn percent(value: u32, total: u32) -> String {
+  if total == 0 {
+    return "0%".to_string();
+  }
+  format!("{:.2}%", value as f64 * 100.0 / total as f64)
+}
+
+fn escape_xml(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
@@ -0,0 +1,36 @@
+use super::csv::escape;
+use super::{timecode_frame_rate, OutputContext};
+use crate::text_event::group_text_events;
+use crate::timecode::Timecode;
+use crate::RecognisedText;
+use std::io::{Result, Write};
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  let frame_rate = timecode_frame_rate(context);
+
+  writeln!(
+    writer,
+    "Marker Name,Description,In,Out,Duration,Marker Type"
+  )?;
+  for event in group_text_events(results, context) {
+    let description = format!(
+      "Text recognised from frame {} to frame {}",
+      event.start_frame, event.end_frame
+    );
+
+    writeln!(
+      writer,
+      "{},{},{},{},{},Comment",
+      escape(&event.single_line()),
+      escape(&description),
+      Timecode::from_milliseconds(event.start_ms, frame_rate),
+      Timecode::from_milliseconds(event.end_ms, frame_rate),
+      Timecode::from_milliseconds(event.end_ms - event.start_ms, frame_rate),
+    )?;
+  }
+  Ok(())
+}
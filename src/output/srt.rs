@@ -0,0 +1,60 @@
+use super::OutputContext;
+use crate::text_event::group_text_events;
+use crate::timecode::format_milliseconds;
+use crate::RecognisedText;
+use std::io::{Result, Write};
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  for (index, cue) in group_text_events(results, context).iter().enumerate() {
+    writeln!(writer, "{}", index + 1)?;
+    writeln!(
+      writer,
+      "{} --> {}",
+      format_milliseconds(cue.start_ms, ','),
+      format_milliseconds(cue.end_ms, ',')
+    )?;
+    for line in cue.lines() {
+      writeln!(writer, "{}", line)?;
+    }
+    writeln!(writer)?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn result(frame: u32, text: &str) -> RecognisedText {
+    RecognisedText {
+      frame,
+      pts: frame as u64 * 40,
+      timestamp_ms: frame as u64 * 40,
+      duration: 40,
+      text: text.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn numbered_cues() {
+    let results = [
+      result(0, "Tom & Jerry\n\nEpisode 1"),
+      result(1, "Tom & Jerry\nEpisode 1"),
+      result(2, ""),
+      result(50, "NEWS"),
+    ];
+    let mut subtitles = vec![];
+    write(&mut subtitles, &results, &OutputContext::default()).unwrap();
+
+    assert_eq!(
+      String::from_utf8(subtitles).unwrap(),
+      "1\n00:00:00,000 --> 00:00:00,080\nTom & Jerry\nEpisode 1\n\n\
+       2\n00:00:02,000 --> 00:00:02,040\nNEWS\n\n"
+    );
+  }
+}
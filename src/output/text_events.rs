@@ -0,0 +1,14 @@
+use super::OutputContext;
+use crate::text_event::group_text_events;
+use crate::RecognisedText;
+use std::io::{Result, Write};
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  let events = group_text_events(results, context);
+  serde_json::to_writer_pretty(writer, &events)?;
+  Ok(())
+}
@@ -0,0 +1,153 @@
+use super::{escape_xml, percent, OutputContext};
+use crate::text_event::group_text_events;
+use crate::timecode::format_milliseconds;
+use crate::RecognisedText;
+use std::io::{Result, Write};
+
+const IMSC1_TEXT_PROFILE: &str = "http://www.w3.org/ns/ttml/profile/imsc1/text";
+
+/// BCP-47 tag of the first Tesseract language, undetermined when it is not known
+fn language_tag(language: &str) -> &'static str {
+  match language.split('+').next().unwrap_or_default() {
+    "ara" => "ar",
+    "ces" => "cs",
+    "chi_sim" => "zh-Hans",
+    "chi_tra" => "zh-Hant",
+    "dan" => "da",
+    "deu" => "de",
+    "ell" => "el",
+    "eng" => "en",
+    "fin" => "fi",
+    "fra" => "fr",
+    "heb" => "he",
+    "hin" => "hi",
+    "hun" => "hu",
+    "ita" => "it",
+    "jpn" => "ja",
+    "kor" => "ko",
+    "nld" => "nl",
+    "nor" => "no",
+    "pol" => "pl",
+    "por" => "pt",
+    "ron" => "ro",
+    "rus" => "ru",
+    "spa" => "es",
+    "swe" => "sv",
+    "tur" => "tr",
+    "ukr" => "uk",
+    "vie" => "vi",
+    _ => "und",
+  }
+}
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  // The layout region matches the region of interest, or the whole frame without one
+  let (origin, extent) = match &context.region {
+    Some(region) => (
+      format!(
+        "{} {}",
+        percent(region.left, context.frame_width),
+        percent(region.top, context.frame_height)
+      ),
+      format!(
+        "{} {}",
+        percent(region.width, context.frame_width),
+        percent(region.height, context.frame_height)
+      ),
+    ),
+    None => ("0% 0%".to_string(), "100% 100%".to_string()),
+  };
+
+  writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+  writeln!(
+    writer,
+    r#"<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" xmlns:tts="http://www.w3.org/ns/ttml#styling" ttp:profile="{}" ttp:timeBase="media" xml:lang="{}">"#,
+    IMSC1_TEXT_PROFILE,
+    language_tag(&context.language)
+  )?;
+  writeln!(writer, "  <head>")?;
+  writeln!(writer, "    <layout>")?;
+  writeln!(
+    writer,
+    r#"      <region xml:id="roi" tts:origin="{}" tts:extent="{}" tts:displayAlign="after"/>"#,
+    origin, extent
+  )?;
+  writeln!(writer, "    </layout>")?;
+  writeln!(writer, "  </head>")?;
+  writeln!(writer, r#"  <body region="roi">"#)?;
+  writeln!(writer, "    <div>")?;
+
+  for (index, cue) in group_text_events(results, context).iter().enumerate() {
+    let lines: Vec<String> = cue.lines().map(escape_xml).collect();
+
+    writeln!(
+      writer,
+      r#"      <p xml:id="text-{}" begin="{}" end="{}">{}</p>"#,
+      index + 1,
+      format_milliseconds(cue.start_ms, '.'),
+      format_milliseconds(cue.end_ms, '.'),
+      lines.join("<br/>")
+    )?;
+  }
+
+  writeln!(writer, "    </div>")?;
+  writeln!(writer, "  </body>")?;
+  writeln!(writer, "</tt>")?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::region::Rectangle;
+
+  fn result(frame: u32, text: &str) -> RecognisedText {
+    RecognisedText {
+      frame,
+      pts: frame as u64 * 40,
+      timestamp_ms: frame as u64 * 40,
+      duration: 40,
+      text: text.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn language_tags() {
+    assert_eq!(language_tag("fra"), "fr");
+    assert_eq!(language_tag("chi_sim+eng"), "zh-Hans");
+    assert_eq!(language_tag("osd"), "und");
+    assert_eq!(language_tag(""), "und");
+  }
+
+  #[test]
+  fn imsc1_document() {
+    let context = OutputContext {
+      language: "eng+fra".to_string(),
+      frame_width: 1920,
+      frame_height: 1080,
+      region: Some(Rectangle {
+        left: 0,
+        top: 810,
+        width: 1920,
+        height: 270,
+      }),
+      ..Default::default()
+    };
+    let results = [result(0, "Tom & Jerry\nEpisode 1"), result(25, "")];
+    let mut document = vec![];
+    write(&mut document, &results, &context).unwrap();
+    let document = String::from_utf8(document).unwrap();
+
+    assert!(document.contains(r#"xml:lang="en">"#));
+    assert!(document.contains(r#"tts:origin="0.00% 75.00%" tts:extent="100.00% 25.00%""#));
+    assert!(document.contains(
+      r#"<p xml:id="text-1" begin="00:00:00.000" end="00:00:01.000">Tom &amp; Jerry<br/>Episode 1</p>"#
+    ));
+    assert!(!document.contains("text-2"));
+  }
+}
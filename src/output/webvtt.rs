@@ -0,0 +1,126 @@
+use super::{percent, OutputContext};
+use crate::text_event::group_text_events;
+use crate::timecode::format_milliseconds;
+use crate::RecognisedText;
+use std::io::{Result, Write};
+
+const REGION_ID: &str = "roi";
+
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+pub fn write<W: Write>(
+  writer: &mut W,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<()> {
+  let cues = group_text_events(results, context);
+
+  writeln!(writer, "WEBVTT")?;
+  writeln!(writer)?;
+
+  // Hint players to render the cues where the text was found in the frame
+  if let Some(region) = &context.region {
+    let lines = cues
+      .iter()
+      .map(|cue| cue.lines().count())
+      .max()
+      .unwrap_or(1);
+
+    writeln!(writer, "REGION")?;
+    writeln!(writer, "id:{}", REGION_ID)?;
+    writeln!(
+      writer,
+      "width:{}",
+      percent(region.width, context.frame_width)
+    )?;
+    writeln!(writer, "lines:{}", lines.max(1))?;
+    writeln!(writer, "regionanchor:0%,0%")?;
+    writeln!(
+      writer,
+      "viewportanchor:{},{}",
+      percent(region.left, context.frame_width),
+      percent(region.top, context.frame_height)
+    )?;
+    writeln!(writer)?;
+  }
+
+  let settings = context
+    .region
+    .map(|_| format!(" region:{}", REGION_ID))
+    .unwrap_or_default();
+
+  for (index, cue) in cues.iter().enumerate() {
+    writeln!(writer, "text-{}", index + 1)?;
+    writeln!(
+      writer,
+      "{} --> {}{}",
+      format_milliseconds(cue.start_ms, '.'),
+      format_milliseconds(cue.end_ms, '.'),
+      settings
+    )?;
+    for line in cue.lines() {
+      writeln!(writer, "{}", escape(line))?;
+    }
+    writeln!(writer)?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::region::Rectangle;
+
+  fn result(frame: u32, text: &str) -> RecognisedText {
+    RecognisedText {
+      frame,
+      pts: frame as u64 * 40,
+      timestamp_ms: frame as u64 * 40,
+      duration: 40,
+      text: text.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn cues_in_the_region_of_interest() {
+    let context = OutputContext {
+      frame_width: 1920,
+      frame_height: 1080,
+      region: Some(Rectangle {
+        left: 0,
+        top: 810,
+        width: 1920,
+        height: 270,
+      }),
+      ..Default::default()
+    };
+    let results = [result(0, "<Tom> & Jerry\nEpisode 1")];
+    let mut subtitles = vec![];
+    write(&mut subtitles, &results, &context).unwrap();
+
+    assert_eq!(
+      String::from_utf8(subtitles).unwrap(),
+      "WEBVTT\n\n\
+       REGION\nid:roi\nwidth:100.00%\nlines:2\nregionanchor:0%,0%\nviewportanchor:0.00%,75.00%\n\n\
+       text-1\n00:00:00.000 --> 00:00:00.040 region:roi\n&lt;Tom&gt; &amp; Jerry\nEpisode 1\n\n"
+    );
+  }
+
+  #[test]
+  fn cues_without_region() {
+    let results = [result(0, "NEWS")];
+    let mut subtitles = vec![];
+    write(&mut subtitles, &results, &OutputContext::default()).unwrap();
+
+    assert_eq!(
+      String::from_utf8(subtitles).unwrap(),
+      "WEBVTT\n\ntext-1\n00:00:00.000 --> 00:00:00.040\nNEWS\n\n"
+    );
+  }
+}
@@ -0,0 +1,53 @@
+use super::escape_xml;
+use super::json::Document;
+use serde_json::Value;
+use std::io::{Error, ErrorKind, Result, Write};
+
+/// Element name of the array items, the singular of the array name
+fn item_name(name: &str) -> &str {
+  if name.len() > 1 && name.ends_with('s') {
+    &name[..name.len() - 1]
+  } else {
+    "item"
+  }
+}
+
+/// Write the JSON value as an element, objects and arrays as nested elements
+fn write_element<W: Write>(writer: &mut W, name: &str, value: &Value, depth: usize) -> Result<()> {
+  let indent = "  ".repeat(depth);
+  match value {
+    Value::Null => Ok(()),
+    Value::Bool(boolean) => writeln!(writer, "{}<{}>{}</{}>", indent, name, boolean, name),
+    Value::Number(number) => writeln!(writer, "{}<{}>{}</{}>", indent, name, number, name),
+    Value::String(text) => writeln!(
+      writer,
+      "{}<{}>{}</{}>",
+      indent,
+      name,
+      escape_xml(text),
+      name
+    ),
+    Value::Array(items) => {
+      writeln!(writer, "{}<{}>", indent, name)?;
+      for item in items {
+        write_element(writer, item_name(name), item, depth + 1)?;
+      }
+      writeln!(writer, "{}</{}>", indent, name)
+    }
+    Value::Object(fields) => {
+      writeln!(writer, "{}<{}>", indent, name)?;
+      for (field, value) in fields {
+        write_element(writer, field, value, depth + 1)?;
+      }
+      writeln!(writer, "{}</{}>", indent, name)
+    }
+  }
+}
+
+pub fn write<W: Write>(writer: &mut W, document: &Document) -> Result<()> {
+  let document =
+    serde_json::to_value(document).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+  writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+  write_element(writer, "job", &document, 0)
+}
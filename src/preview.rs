@@ -0,0 +1,130 @@
+use crate::output::OutputContext;
+use crate::text_event::group_text_events;
+use crate::RecognisedText;
+use mcai_worker_sdk::{info, JsonSchema, MessageError};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::{self, Command};
+
+/// QC video, with the recognised text and line bounding boxes drawn over the frames
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PreviewParameters {
+  /// Path of the annotated video
+  path: String,
+  /// Video to annotate instead of the source, like a lower resolution proxy
+  proxy_path: Option<String>,
+  /// FFmpeg executable (default: ffmpeg)
+  ffmpeg: Option<String>,
+}
+
+fn to_error(path: &Path, error: std::io::Error) -> MessageError {
+  MessageError::RuntimeError(format!("Unable to write {}: {}", path.display(), error))
+}
+
+/// Filter option values are quoted, so only the quotes have to be escaped
+fn quote(value: &str) -> String {
+  format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Time interval during which a filter is enabled, in seconds
+fn enable(start_ms: u64, end_ms: u64) -> String {
+  format!(
+    "enable='between(t,{:.3},{:.3})'",
+    start_ms as f64 / 1000.0,
+    end_ms as f64 / 1000.0
+  )
+}
+
+/// Build the FFmpeg filters drawing the results, and encode the annotated video
+pub fn render_preview(
+  parameters: &PreviewParameters,
+  results: &[RecognisedText],
+  context: &OutputContext,
+) -> Result<(), MessageError> {
+  let directory = env::temp_dir().join(format!("text-recognition-preview-{}", process::id()));
+  fs::create_dir_all(&directory).map_err(|error| to_error(&directory, error))?;
+
+  let mut filters = vec![];
+
+  // positions are relative to the source dimensions, to draw them on a proxy too
+  let frame_width = context.frame_width.max(1);
+  let frame_height = context.frame_height.max(1);
+  for (index, result) in results.iter().enumerate() {
+    let end_pts = results
+      .get(index + 1)
+      .map(|next| next.pts)
+      .unwrap_or(result.pts + result.duration);
+    let interval = enable(
+      context.time_base.to_milliseconds(result.pts),
+      context.time_base.to_milliseconds(end_pts),
+    );
+
+    for line in &result.lines {
+      let bounding_box = &line.bounding_box;
+      filters.push(format!(
+        "drawbox=x=iw*{}/{}:y=ih*{}/{}:w=iw*{}/{}:h=ih*{}/{}:color=red@0.8:t=2:{}",
+        bounding_box.left,
+        frame_width,
+        bounding_box.top,
+        frame_height,
+        bounding_box.width,
+        frame_width,
+        bounding_box.height,
+        frame_height,
+        interval
+      ));
+    }
+  }
+
+  for (index, event) in group_text_events(results, context).iter().enumerate() {
+    let text_path = directory.join(format!("text-{}.txt", index));
+    fs::write(&text_path, &event.text).map_err(|error| to_error(&text_path, error))?;
+
+    filters.push(format!(
+      "drawtext=textfile={}:expansion=none:fontcolor=white:fontsize=h/24:box=1:boxcolor=black@0.6:x=(w-text_w)/2:y=h-text_h-h/20:{}",
+      quote(&text_path.to_string_lossy()),
+      enable(event.start_ms, event.end_ms)
+    ));
+  }
+
+  if filters.is_empty() {
+    filters.push("null".to_string());
+  }
+
+  let script_path = directory.join("filters.txt");
+  fs::write(&script_path, filters.join(",\n")).map_err(|error| to_error(&script_path, error))?;
+
+  let input = parameters
+    .proxy_path
+    .as_ref()
+    .unwrap_or(&context.source_path);
+  let ffmpeg = parameters.ffmpeg.as_deref().unwrap_or("ffmpeg");
+  info!("Render the annotated preview {}", parameters.path);
+
+  let status = Command::new(ffmpeg)
+    .args(["-y", "-v", "error", "-i", input].iter())
+    .arg("-filter_script:v")
+    .arg(&script_path)
+    .args(
+      [
+        "-c:v", "libx264", "-preset", "veryfast", "-crf", "28", "-pix_fmt", "yuv420p", "-an",
+      ]
+      .iter(),
+    )
+    .arg(&parameters.path)
+    .status();
+  let _ = fs::remove_dir_all(&directory);
+
+  match status {
+    Ok(status) if status.success() => Ok(()),
+    Ok(status) => Err(MessageError::RuntimeError(format!(
+      "Unable to render the preview {}: {} exited with {}",
+      parameters.path, ffmpeg, status
+    ))),
+    Err(error) => Err(MessageError::RuntimeError(format!(
+      "Unable to render the preview {}: {}",
+      parameters.path, error
+    ))),
+  }
+}
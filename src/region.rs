@@ -0,0 +1,445 @@
+use mcai_worker_sdk::{JsonSchema, RegionOfInterest};
+
+/// Area of the source frame, in pixels
+#[derive(Clone, Copy, Debug, Default, JsonSchema, PartialEq, Serialize)]
+pub struct Rectangle {
+  pub left: u32,
+  pub top: u32,
+  pub width: u32,
+  pub height: u32,
+}
+
+impl Rectangle {
+  /// Smallest rectangle containing the points of a polygon, in pixels
+  pub fn bounding_polygon(points: impl Iterator<Item = (f64, f64)>) -> Self {
+    let (left, top, right, bottom) = points.fold(
+      (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+      |(left, top, right, bottom), (x, y)| (left.min(x), top.min(y), right.max(x), bottom.max(y)),
+    );
+    if left > right || top > bottom {
+      return Rectangle::default();
+    }
+
+    let (left, top) = (left.max(0.0), top.max(0.0));
+    Rectangle {
+      left: left as u32,
+      top: top as u32,
+      width: (right - left).max(0.0).round() as u32,
+      height: (bottom - top).max(0.0).round() as u32,
+    }
+  }
+
+  /// Resolve a region of interest against the source frame dimensions.
+  /// `right` and `bottom` are margins from the frame edges, `width` and `height` take precedence over them.
+  pub fn from_region_of_interest(
+    region_of_interest: &RegionOfInterest,
+    frame_width: u32,
+    frame_height: u32,
+  ) -> Self {
+    let left = region_of_interest.left.unwrap_or(0).min(frame_width);
+    let top = region_of_interest.top.unwrap_or(0).min(frame_height);
+
+    let width = region_of_interest
+      .width
+      .unwrap_or_else(|| frame_width.saturating_sub(left + region_of_interest.right.unwrap_or(0)));
+    let height = region_of_interest
+      .height
+      .unwrap_or_else(|| frame_height.saturating_sub(top + region_of_interest.bottom.unwrap_or(0)));
+
+    Rectangle {
+      left,
+      top,
+      width: width.min(frame_width - left),
+      height: height.min(frame_height - top),
+    }
+  }
+
+  /// Resolve a region of interest set on the frame displayed rotated clockwise by `rotation`
+  /// degrees against the stored frame dimensions, giving the area of the stored frame.
+  pub fn from_displayed_region_of_interest(
+    region_of_interest: &RegionOfInterest,
+    rotation: u32,
+    frame_width: u32,
+    frame_height: u32,
+  ) -> Self {
+    let (displayed_width, displayed_height) =
+      displayed_dimensions(rotation, frame_width, frame_height);
+    let displayed =
+      Rectangle::from_region_of_interest(region_of_interest, displayed_width, displayed_height);
+    let frame = Rectangle {
+      left: 0,
+      top: 0,
+      width: frame_width,
+      height: frame_height,
+    };
+    SourceMapping {
+      processed_width: frame_width,
+      processed_height: frame_height,
+      source_area: frame,
+      rotation,
+    }
+    .rectangle(displayed)
+  }
+
+  /// Region of interest set with the absolute position and size of the rectangle
+  pub fn to_region_of_interest(self) -> RegionOfInterest {
+    RegionOfInterest {
+      left: Some(self.left),
+      top: Some(self.top),
+      width: Some(self.width),
+      height: Some(self.height),
+      right: None,
+      bottom: None,
+    }
+  }
+
+  /// Area shared by both rectangles, None when they do not overlap
+  pub fn intersection(&self, other: &Rectangle) -> Option<Self> {
+    let left = self.left.max(other.left);
+    let top = self.top.max(other.top);
+    let right = (self.left + self.width).min(other.left + other.width);
+    let bottom = (self.top + self.height).min(other.top + other.height);
+    if right <= left || bottom <= top {
+      return None;
+    }
+
+    Some(Rectangle {
+      left,
+      top,
+      width: right - left,
+      height: bottom - top,
+    })
+  }
+
+  /// Smallest rectangle containing both rectangles
+  pub fn union(&self, other: &Rectangle) -> Self {
+    let left = self.left.min(other.left);
+    let top = self.top.min(other.top);
+    let right = (self.left + self.width).max(other.left + other.width);
+    let bottom = (self.top + self.height).max(other.top + other.height);
+
+    Rectangle {
+      left,
+      top,
+      width: right - left,
+      height: bottom - top,
+    }
+  }
+}
+
+/// Dimensions of a frame displayed rotated clockwise by `rotation` degrees
+pub fn displayed_dimensions(rotation: u32, frame_width: u32, frame_height: u32) -> (u32, u32) {
+  match rotation {
+    90 | 270 => (frame_height, frame_width),
+    _ => (frame_width, frame_height),
+  }
+}
+
+/// Position in a frame, in pixels
+#[derive(Clone, Copy, Debug, Default, JsonSchema, PartialEq, Serialize)]
+pub struct Point {
+  pub x: u32,
+  pub y: u32,
+}
+
+/// Mapping of the processed image coordinates back into the source frame,
+/// the processed image being `source_area` cropped and resized to `processed_width`x`processed_height`,
+/// then rotated clockwise by `rotation` degrees before the recognition.
+#[derive(Clone, Copy, Debug)]
+pub struct SourceMapping {
+  pub processed_width: u32,
+  pub processed_height: u32,
+  pub source_area: Rectangle,
+  pub rotation: u32,
+}
+
+impl SourceMapping {
+  fn scale(value: u32, source: u32, processed: u32) -> u32 {
+    if processed == 0 {
+      return value;
+    }
+    ((value as u64 * source as u64 + processed as u64 / 2) / processed as u64) as u32
+  }
+
+  fn scale_x(&self, value: u32) -> u32 {
+    Self::scale(value, self.source_area.width, self.processed_width)
+  }
+
+  fn scale_y(&self, value: u32) -> u32 {
+    Self::scale(value, self.source_area.height, self.processed_height)
+  }
+
+  /// Point of the processed image before its rotation
+  fn unrotate_point(&self, point: Point) -> Point {
+    let (width, height) = (self.processed_width, self.processed_height);
+    let (x, y) = match self.rotation {
+      90 => (point.y, height.saturating_sub(point.x)),
+      180 => (
+        width.saturating_sub(point.x),
+        height.saturating_sub(point.y),
+      ),
+      270 => (width.saturating_sub(point.y), point.x),
+      _ => (point.x, point.y),
+    };
+    Point { x, y }
+  }
+
+  /// Rectangle of the processed image before its rotation
+  fn unrotate_rectangle(&self, rectangle: Rectangle) -> Rectangle {
+    let (width, height) = (self.processed_width, self.processed_height);
+    let right = rectangle.left + rectangle.width;
+    let bottom = rectangle.top + rectangle.height;
+    match self.rotation {
+      90 => Rectangle {
+        left: rectangle.top,
+        top: height.saturating_sub(right),
+        width: rectangle.height,
+        height: rectangle.width,
+      },
+      180 => Rectangle {
+        left: width.saturating_sub(right),
+        top: height.saturating_sub(bottom),
+        width: rectangle.width,
+        height: rectangle.height,
+      },
+      270 => Rectangle {
+        left: width.saturating_sub(bottom),
+        top: rectangle.left,
+        width: rectangle.height,
+        height: rectangle.width,
+      },
+      _ => rectangle,
+    }
+  }
+
+  /// Rectangle of the processed image rotated, from the processed image before its rotation
+  fn rotate_rectangle(&self, rectangle: Rectangle) -> Rectangle {
+    let (width, height) = (self.processed_width, self.processed_height);
+    let right = rectangle.left + rectangle.width;
+    let bottom = rectangle.top + rectangle.height;
+    match self.rotation {
+      90 => Rectangle {
+        left: height.saturating_sub(bottom),
+        top: rectangle.left,
+        width: rectangle.height,
+        height: rectangle.width,
+      },
+      180 => Rectangle {
+        left: width.saturating_sub(right),
+        top: height.saturating_sub(bottom),
+        width: rectangle.width,
+        height: rectangle.height,
+      },
+      270 => Rectangle {
+        left: rectangle.top,
+        top: width.saturating_sub(right),
+        width: rectangle.height,
+        height: rectangle.width,
+      },
+      _ => rectangle,
+    }
+  }
+
+  /// Rectangle of the source frame located in the processed image, None when it is outside
+  /// of the processed area
+  pub fn processed_rectangle(&self, rectangle: Rectangle) -> Option<Rectangle> {
+    let rectangle = rectangle.intersection(&self.source_area)?;
+    let (source_width, source_height) = (self.source_area.width, self.source_area.height);
+    let rectangle = Rectangle {
+      left: Self::scale(
+        rectangle.left - self.source_area.left,
+        self.processed_width,
+        source_width,
+      ),
+      top: Self::scale(
+        rectangle.top - self.source_area.top,
+        self.processed_height,
+        source_height,
+      ),
+      width: Self::scale(rectangle.width, self.processed_width, source_width),
+      height: Self::scale(rectangle.height, self.processed_height, source_height),
+    };
+    Some(self.rotate_rectangle(rectangle))
+  }
+
+  pub fn point(&self, point: Point) -> Point {
+    let point = self.unrotate_point(point);
+    Point {
+      x: self.source_area.left + self.scale_x(point.x),
+      y: self.source_area.top + self.scale_y(point.y),
+    }
+  }
+
+  pub fn rectangle(&self, rectangle: Rectangle) -> Rectangle {
+    let rectangle = self.unrotate_rectangle(rectangle);
+    Rectangle {
+      left: self.source_area.left + self.scale_x(rectangle.left),
+      top: self.source_area.top + self.scale_y(rectangle.top),
+      width: self.scale_x(rectangle.width),
+      height: self.scale_y(rectangle.height),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rectangle(left: u32, top: u32, width: u32, height: u32) -> Rectangle {
+    Rectangle {
+      left,
+      top,
+      width,
+      height,
+    }
+  }
+
+  /// Area of 800x400 pixels of the source frame, processed at half its size
+  fn mapping(rotation: u32) -> SourceMapping {
+    SourceMapping {
+      processed_width: 400,
+      processed_height: 200,
+      source_area: rectangle(100, 50, 800, 400),
+      rotation,
+    }
+  }
+
+  #[test]
+  fn region_of_interest_margins() {
+    let region_of_interest = RegionOfInterest {
+      left: Some(100),
+      top: Some(50),
+      width: None,
+      height: Some(5000),
+      right: Some(20),
+      bottom: None,
+    };
+
+    assert_eq!(
+      Rectangle::from_region_of_interest(&region_of_interest, 1920, 1080),
+      rectangle(100, 50, 1800, 1030)
+    );
+  }
+
+  #[test]
+  fn intersection_and_union() {
+    let left = rectangle(0, 0, 100, 50);
+    let right = rectangle(80, 40, 100, 50);
+
+    assert_eq!(left.intersection(&right), Some(rectangle(80, 40, 20, 10)));
+    assert_eq!(left.union(&right), rectangle(0, 0, 180, 90));
+    assert_eq!(left.intersection(&rectangle(100, 0, 10, 10)), None);
+  }
+
+  #[test]
+  fn bounding_polygon() {
+    let points = vec![(10.0, 20.0), (50.5, 18.0), (48.0, 40.0), (-2.0, 38.0)];
+
+    assert_eq!(
+      Rectangle::bounding_polygon(points.into_iter()),
+      rectangle(0, 18, 51, 22)
+    );
+    assert_eq!(
+      Rectangle::bounding_polygon(std::iter::empty()),
+      Rectangle::default()
+    );
+  }
+
+  #[test]
+  fn source_mapping_of_the_cropped_and_resized_frames() {
+    let mapping = mapping(0);
+
+    assert_eq!(
+      mapping.rectangle(rectangle(10, 20, 40, 30)),
+      rectangle(120, 90, 80, 60)
+    );
+    assert_eq!(
+      mapping.point(Point { x: 10, y: 20 }),
+      Point { x: 120, y: 90 }
+    );
+  }
+
+  #[test]
+  fn source_mapping_of_the_rotated_frames() {
+    // the top left corner of the frame rotated by 90 degrees is its bottom left corner
+    assert_eq!(
+      mapping(90).rectangle(rectangle(0, 0, 20, 10)),
+      rectangle(100, 410, 20, 40)
+    );
+    assert_eq!(
+      mapping(180).rectangle(rectangle(0, 0, 20, 10)),
+      rectangle(860, 430, 40, 20)
+    );
+    assert_eq!(
+      mapping(270).rectangle(rectangle(0, 0, 20, 10)),
+      rectangle(880, 50, 20, 40)
+    );
+    assert_eq!(
+      mapping(90).point(Point { x: 0, y: 0 }),
+      Point { x: 100, y: 450 }
+    );
+  }
+
+  #[test]
+  fn processed_rectangles_of_the_source_frame() {
+    assert_eq!(
+      mapping(0).processed_rectangle(rectangle(120, 90, 80, 60)),
+      Some(rectangle(10, 20, 40, 30))
+    );
+    assert_eq!(
+      mapping(0).processed_rectangle(rectangle(0, 0, 150, 100)),
+      Some(rectangle(0, 0, 25, 25))
+    );
+    assert_eq!(
+      mapping(0).processed_rectangle(rectangle(0, 0, 50, 50)),
+      None
+    );
+
+    for &rotation in &[0, 90, 180, 270] {
+      let processed = rectangle(10, 20, 40, 30);
+      let source = mapping(rotation).rectangle(processed);
+      assert_eq!(
+        mapping(rotation).processed_rectangle(source),
+        Some(processed)
+      );
+    }
+  }
+
+  #[test]
+  fn displayed_region_of_interest() {
+    // the bottom band of a 1080x1920 portrait frame stored as 1920x1080, rotated by 90 degrees
+    let region_of_interest = RegionOfInterest {
+      left: None,
+      top: Some(1600),
+      width: None,
+      height: None,
+      right: None,
+      bottom: None,
+    };
+    let area = Rectangle::from_displayed_region_of_interest(&region_of_interest, 90, 1920, 1080);
+    assert_eq!(
+      area,
+      Rectangle {
+        left: 1600,
+        top: 0,
+        width: 320,
+        height: 1080,
+      }
+    );
+
+    let area = Rectangle::from_displayed_region_of_interest(&region_of_interest, 270, 1920, 1080);
+    assert_eq!(area.left, 0);
+    assert_eq!(area.width, 320);
+
+    let area = Rectangle::from_displayed_region_of_interest(&region_of_interest, 0, 1080, 1920);
+    assert_eq!(
+      area,
+      Rectangle {
+        left: 0,
+        top: 1600,
+        width: 1080,
+        height: 320,
+      }
+    );
+  }
+}
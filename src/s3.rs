@@ -0,0 +1,138 @@
+use crate::aws::AwsCredentials;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use std::fs::File;
+use std::path::Path;
+
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// S3 credentials and endpoint, read from the environment when not set
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct S3Parameters {
+  /// Access key identifier (default: AWS_ACCESS_KEY_ID environment variable)
+  access_key_id: Option<String>,
+  /// Secret access key (default: AWS_SECRET_ACCESS_KEY environment variable)
+  secret_access_key: Option<String>,
+  /// Region of the bucket (default: AWS_REGION environment variable, or us-east-1)
+  region: Option<String>,
+  /// Endpoint of an S3 compatible storage, like http://localhost:9000 (default: AWS endpoint of the region)
+  endpoint: Option<String>,
+}
+
+/// Object location, from an `s3://bucket/key` URL
+#[derive(Clone, Debug, PartialEq)]
+pub struct S3Location {
+  pub bucket: String,
+  pub key: String,
+}
+
+impl S3Location {
+  pub fn parse(url: &str) -> Option<Self> {
+    if !url.starts_with("s3://") {
+      return None;
+    }
+    let path = &url["s3://".len()..];
+    let separator = path.find('/')?;
+    let (bucket, key) = (&path[..separator], &path[separator + 1..]);
+    if bucket.is_empty() || key.is_empty() {
+      return None;
+    }
+
+    Some(S3Location {
+      bucket: bucket.to_string(),
+      key: key.to_string(),
+    })
+  }
+}
+
+impl std::fmt::Display for S3Location {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "s3://{}/{}", self.bucket, self.key)
+  }
+}
+
+/// Minimal S3 client, signing the requests with AWS Signature Version 4
+#[derive(Debug)]
+pub struct S3Client {
+  credentials: AwsCredentials,
+  endpoint: String,
+}
+
+/// Percent-encode the object key, keeping the path separators
+fn encode_key(key: &str) -> String {
+  key
+    .bytes()
+    .map(|byte| match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+        (byte as char).to_string()
+      }
+      _ => format!("%{:02X}", byte),
+    })
+    .collect()
+}
+
+impl S3Client {
+  pub fn new(parameters: &S3Parameters) -> Result<Self, MessageError> {
+    let credentials = AwsCredentials::new(
+      &parameters.access_key_id,
+      &parameters.secret_access_key,
+      &parameters.region,
+    )?;
+    let endpoint = parameters
+      .endpoint
+      .clone()
+      .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", credentials.region));
+
+    Ok(S3Client {
+      credentials,
+      endpoint: endpoint.trim_end_matches('/').to_string(),
+    })
+  }
+
+  /// Signed request on the object, using the path-style URL
+  fn request(&self, method: &str, location: &S3Location) -> ureq::Request {
+    let path = format!("/{}/{}", location.bucket, encode_key(&location.key));
+    let host = self.endpoint.splitn(2, "://").last().unwrap_or_default();
+
+    let mut request = ureq::request(method, &format!("{}{}", self.endpoint, path));
+    for (name, value) in
+      self
+        .credentials
+        .signed_headers("s3", method, host, &path, UNSIGNED_PAYLOAD)
+    {
+      request.set(name, &value);
+    }
+    request
+  }
+
+  /// Upload the local file as the object
+  pub fn put_object(&self, location: &S3Location, path: &Path) -> Result<(), MessageError> {
+    let file = File::open(path).map_err(|error| {
+      MessageError::RuntimeError(format!("Unable to open {}: {}", path.display(), error))
+    })?;
+    let length = file
+      .metadata()
+      .map(|metadata| metadata.len())
+      .map_err(|error| {
+        MessageError::RuntimeError(format!("Unable to open {}: {}", path.display(), error))
+      })?;
+
+    let response = self
+      .request("PUT", location)
+      .set("Content-Length", &length.to_string())
+      .send(file);
+    if let Some(error) = response.synthetic_error() {
+      return Err(MessageError::RuntimeError(format!(
+        "Unable to upload {}: {}",
+        location, error
+      )));
+    }
+    if response.error() {
+      return Err(MessageError::RuntimeError(format!(
+        "Unable to upload {}: {}",
+        location,
+        response.status_line()
+      )));
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,290 @@
+use crate::ocr::{luma, FrameBuffer};
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use std::collections::HashSet;
+use std::time::Instant;
+
+/// Delay behind the live edge from which the frames are skipped, in milliseconds
+const MAX_LIVE_DELAY_MS: u64 = 1000;
+
+/// Frame of the explicit list of the recognised frames
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameSelector {
+  /// Number of the decoded frame, from 0, as reported in the results
+  Number(u32),
+  /// Presentation timestamp of the frame, in the time base of the video stream
+  Pts(u64),
+}
+
+/// Numbers and timestamps of the frames known to contain text, like the slates of a QC workflow
+#[derive(Debug)]
+pub struct FrameList {
+  numbers: HashSet<u32>,
+  timestamps: HashSet<u64>,
+}
+
+impl FrameList {
+  pub fn new(frames: &[FrameSelector]) -> Self {
+    let mut numbers = HashSet::new();
+    let mut timestamps = HashSet::new();
+    for frame in frames {
+      match frame {
+        FrameSelector::Number(number) => numbers.insert(*number),
+        FrameSelector::Pts(pts) => timestamps.insert(*pts),
+      };
+    }
+
+    FrameList {
+      numbers,
+      timestamps,
+    }
+  }
+
+  /// Whether the frame is listed, by its number or its timestamp
+  pub fn contains(&self, frame_count: u32, pts: u64) -> bool {
+    self.numbers.contains(&frame_count) || self.timestamps.contains(&pts)
+  }
+}
+
+/// Time range of the source recognised in a job of several segments, like the intro,
+/// an ad break or the credits
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct Segment {
+  /// Start of the segment, in milliseconds
+  start: u64,
+  /// End of the segment, excluded, in milliseconds
+  end: u64,
+  /// Label of the segment, reported with its results
+  label: Option<String>,
+}
+
+/// Disjoint segments of the source, the frames outside of them not being recognised
+#[derive(Clone, Debug)]
+pub struct Segments {
+  segments: Vec<Segment>,
+}
+
+impl Segments {
+  pub fn new(segments: &[Segment]) -> Result<Self, MessageError> {
+    let mut segments = segments.to_vec();
+    segments.sort_by_key(|segment| segment.start);
+    for segment in &segments {
+      if segment.start >= segment.end {
+        return Err(MessageError::ParameterValueError(format!(
+          "Invalid segment from {} to {} ms, its start must precede its end",
+          segment.start, segment.end
+        )));
+      }
+    }
+    if let Some(overlapping) = segments.windows(2).find(|pair| pair[1].start < pair[0].end) {
+      return Err(MessageError::ParameterValueError(format!(
+        "The segment starting at {} ms overlaps the one ending at {} ms",
+        overlapping[1].start, overlapping[0].end
+      )));
+    }
+
+    Ok(Segments { segments })
+  }
+
+  /// Segment of the timestamp, in milliseconds, with its label
+  pub fn find(&self, timestamp_ms: u64) -> Option<Option<&str>> {
+    self
+      .index(timestamp_ms)
+      .map(|index| self.segments[index].label.as_deref())
+  }
+
+  /// Position of the segment of the timestamp, in milliseconds, in the sorted segments
+  pub fn index(&self, timestamp_ms: u64) -> Option<usize> {
+    self
+      .segments
+      .iter()
+      .position(|segment| (segment.start..segment.end).contains(&timestamp_ms))
+  }
+}
+
+/// Recognition of one frame per shot, the shots being cut where the scene score of the frames
+/// exceeds the threshold, like with the select filter of FFmpeg
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct SceneChangeParameters {
+  /// Scene score from which a frame starts a new shot, from 0 to 1 (default: 0.3)
+  threshold: Option<f64>,
+}
+
+/// Scene score of the decoded frames, computed on their luminance
+#[derive(Debug)]
+pub struct SceneDetector {
+  threshold: f64,
+  previous: Option<Vec<u8>>,
+  previous_difference: f64,
+}
+
+impl SceneDetector {
+  pub fn new(parameters: &SceneChangeParameters) -> Result<Self, MessageError> {
+    let threshold = parameters.threshold.unwrap_or(0.3);
+    if !(0.0..=1.0).contains(&threshold) {
+      return Err(MessageError::ParameterValueError(format!(
+        "Invalid scene change threshold {}, expected a score from 0 to 1",
+        threshold
+      )));
+    }
+
+    Ok(SceneDetector {
+      threshold,
+      previous: None,
+      previous_difference: 0.0,
+    })
+  }
+
+  /// Whether the frame starts a new shot, the first frame starting the first one
+  pub fn is_new_shot(&mut self, buffer: &FrameBuffer) -> bool {
+    let luma = luma(buffer);
+    let score = match &self.previous {
+      Some(previous) if previous.len() == luma.len() && !luma.is_empty() => {
+        // the score ignores the steady motion
+        let difference = mean_difference(previous, &luma);
+        let score =
+          (difference.min((difference - self.previous_difference).abs()) / 100.0).clamp(0.0, 1.0);
+        self.previous_difference = difference;
+        score
+      }
+      _ => 1.0,
+    };
+    self.previous = Some(luma);
+    score >= self.threshold
+  }
+}
+
+/// Skipping of the frames without motion within the region of interest, like the ones of the
+/// mostly static monitoring feeds
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct MotionParameters {
+  /// Mean difference of the luminance with the previous frame below which the frame is not
+  /// recognised, from 0 to 255 (default: 2)
+  threshold: Option<f64>,
+}
+
+/// Luminance of the previous frame
+#[derive(Debug)]
+pub struct MotionDetector {
+  threshold: f64,
+  previous: Option<Vec<u8>>,
+}
+
+impl MotionDetector {
+  pub fn new(parameters: &MotionParameters) -> Self {
+    MotionDetector {
+      threshold: parameters.threshold.unwrap_or(2.0),
+      previous: None,
+    }
+  }
+
+  /// Whether the frame changed from the previous one, the first frame being a change
+  pub fn has_motion(&mut self, buffer: &FrameBuffer) -> bool {
+    let luma = luma(buffer);
+    let motion = match &self.previous {
+      Some(previous) if previous.len() == luma.len() && !luma.is_empty() => {
+        mean_difference(previous, &luma) >= self.threshold
+      }
+      _ => true,
+    };
+    self.previous = Some(luma);
+    motion
+  }
+}
+
+/// Mean absolute difference of the luminance of two frames
+fn mean_difference(previous: &[u8], luma: &[u8]) -> f64 {
+  previous
+    .iter()
+    .zip(luma)
+    .map(|(previous, value)| (*previous as i32 - *value as i32).unsigned_abs() as u64)
+    .sum::<u64>() as f64
+    / luma.len() as f64
+}
+
+/// Skipping of the frames looking like the previous recognised one, like the static slates and
+/// title cards
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct SimilarFrameParameters {
+  /// Maximum number of different bits between the 64 bits perceptual hashes of the similar
+  /// frames (default: 4)
+  max_distance: Option<u32>,
+}
+
+/// Perceptual hash of the last recognised frame
+#[derive(Debug)]
+pub struct SimilarFrameFilter {
+  max_distance: u32,
+  previous: Option<u64>,
+}
+
+impl SimilarFrameFilter {
+  pub fn new(parameters: &SimilarFrameParameters) -> Self {
+    SimilarFrameFilter {
+      max_distance: parameters.max_distance.unwrap_or(4),
+      previous: None,
+    }
+  }
+
+  /// Whether the frame looks like the previous recognised one, the other frames being the ones
+  /// to recognise
+  pub fn is_similar(&mut self, buffer: &FrameBuffer) -> bool {
+    let hash = perceptual_hash(buffer);
+    if let Some(previous) = self.previous {
+      if (previous ^ hash).count_ones() <= self.max_distance {
+        return true;
+      }
+    }
+    self.previous = Some(hash);
+    false
+  }
+}
+
+/// Difference hash of the frame, from the luminance gradients of the frame reduced to 9x8 cells
+fn perceptual_hash(buffer: &FrameBuffer) -> u64 {
+  let (width, height) = (buffer.width as usize, buffer.height as usize);
+  let luma = luma(buffer);
+  let mut sums = [0u64; 72];
+  let mut counts = [0u64; 72];
+  for y in 0..height {
+    let row = y * 8 / height;
+    for x in 0..width {
+      let cell = row * 9 + x * 9 / width;
+      sums[cell] += luma[y * width + x] as u64;
+      counts[cell] += 1;
+    }
+  }
+  let means: Vec<u64> = sums
+    .iter()
+    .zip(&counts)
+    .map(|(sum, count)| sum / (*count).max(1))
+    .collect();
+
+  let mut hash = 0;
+  for row in 0..8 {
+    for column in 0..8 {
+      hash <<= 1;
+      if means[row * 9 + column] < means[row * 9 + column + 1] {
+        hash |= 1;
+      }
+    }
+  }
+  hash
+}
+
+/// Skipping of the frames of a live source when the recognition falls behind its live edge
+#[derive(Debug, Default)]
+pub struct LiveEdge {
+  /// Time and timestamp of the first frame
+  start: Option<(Instant, u64)>,
+}
+
+impl LiveEdge {
+  /// Whether the frame is late, the recognition being slower than the feed
+  pub fn is_late(&mut self, timestamp_ms: u64) -> bool {
+    let (started, start_ms) = *self
+      .start
+      .get_or_insert_with(|| (Instant::now(), timestamp_ms));
+    started.elapsed().as_millis() as u64 > timestamp_ms.saturating_sub(start_ms) + MAX_LIVE_DELAY_MS
+  }
+}
@@ -0,0 +1,73 @@
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use regex::Regex;
+
+/// Characters of the scores and game clocks
+pub const SCOREBOARD_CHARACTERS: &str = "0123456789:.-";
+
+/// Preset recognising the digits of a scoreboard as a single line
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct ScoreboardParameters {
+  /// Regular expression of the scoreboard line, with the home, away and clock named groups,
+  /// like `(?P<home>\d+)-(?P<away>\d+) (?P<clock>\d+:\d\d)`
+  template: Option<String>,
+}
+
+/// Fields of the scoreboard read with the template
+#[derive(Clone, Debug, Default, JsonSchema, PartialEq, Serialize)]
+pub struct Scoreboard {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub home: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub away: Option<u32>,
+  /// Game clock, as recognised
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub clock: Option<String>,
+}
+
+/// Template of the scoreboard line
+#[derive(Debug)]
+pub struct ScoreboardTemplate {
+  regex: Regex,
+}
+
+impl ScoreboardParameters {
+  pub fn template(&self) -> Result<Option<ScoreboardTemplate>, MessageError> {
+    let template = match &self.template {
+      Some(template) => template,
+      None => return Ok(None),
+    };
+
+    let regex = Regex::new(template).map_err(|error| {
+      MessageError::ParameterValueError(format!(
+        "Invalid scoreboard template {}: {}",
+        template, error
+      ))
+    })?;
+    if !regex
+      .capture_names()
+      .flatten()
+      .any(|name| ["home", "away", "clock"].contains(&name))
+    {
+      return Err(MessageError::ParameterValueError(format!(
+        "The scoreboard template {} has no home, away or clock group",
+        template
+      )));
+    }
+    Ok(Some(ScoreboardTemplate { regex }))
+  }
+}
+
+impl ScoreboardTemplate {
+  /// Scoreboard fields of the recognised text, None when it does not match the template
+  pub fn read(&self, text: &str) -> Option<Scoreboard> {
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    let captures = self.regex.captures(&text)?;
+    let group = |name: &str| captures.name(name).map(|group| group.as_str());
+
+    Some(Scoreboard {
+      home: group("home").and_then(|home| home.parse().ok()),
+      away: group("away").and_then(|away| away.parse().ok()),
+      clock: group("clock").map(str::to_string),
+    })
+  }
+}
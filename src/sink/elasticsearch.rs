@@ -0,0 +1,149 @@
+use crate::output::OutputContext;
+use crate::text_event::group_text_events;
+use crate::RecognisedText;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+
+/// Number of documents sent in each bulk request
+const BULK_SIZE: usize = 100;
+
+/// Elasticsearch index receiving the results
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ElasticsearchParameters {
+  /// URL of the Elasticsearch cluster, like http://localhost:9200
+  url: String,
+  /// Name of the index receiving the documents
+  index: String,
+  /// User name for the basic authentication
+  username: Option<String>,
+  /// Password for the basic authentication
+  password: Option<String>,
+  /// Index a document per text event at the end of the job, rather than one per frame (default: false)
+  events: Option<bool>,
+}
+
+/// Indexed document, identifying the job and the source of the result
+#[derive(Serialize)]
+struct Document<'a, T> {
+  job_id: u64,
+  source_path: &'a str,
+  #[serde(flatten)]
+  content: &'a T,
+}
+
+/// Send the results to Elasticsearch with bulk requests
+#[derive(Debug)]
+pub struct ElasticsearchSink {
+  parameters: ElasticsearchParameters,
+  bulk: String,
+  pending: usize,
+}
+
+impl ElasticsearchSink {
+  pub fn new(parameters: ElasticsearchParameters) -> Self {
+    ElasticsearchSink {
+      parameters,
+      bulk: String::new(),
+      pending: 0,
+    }
+  }
+
+  /// Whether the text events are indexed, requiring to keep all the frame results
+  pub fn indexes_events(&self) -> bool {
+    self.parameters.events.unwrap_or(false)
+  }
+
+  /// Queue the frame result, unless indexing the text events
+  pub fn index_frame(
+    &mut self,
+    result: &RecognisedText,
+    context: &OutputContext,
+  ) -> Result<(), MessageError> {
+    if self.indexes_events() {
+      return Ok(());
+    }
+    self.push(result, context)
+  }
+
+  /// Index the queued documents, with the text events when requested
+  pub fn finish(
+    &mut self,
+    results: &[RecognisedText],
+    context: &OutputContext,
+  ) -> Result<(), MessageError> {
+    if self.indexes_events() {
+      for event in group_text_events(results, context) {
+        self.push(&event, context)?;
+      }
+    }
+    self.flush()
+  }
+
+  fn push<T: serde::Serialize>(
+    &mut self,
+    content: &T,
+    context: &OutputContext,
+  ) -> Result<(), MessageError> {
+    let action = json!({ "index": { "_index": self.parameters.index } });
+    let document = serde_json::to_string(&Document {
+      job_id: context.job_id,
+      source_path: &context.source_path,
+      content,
+    })
+    .map_err(|error| {
+      MessageError::RuntimeError(format!("Unable to serialize the document: {}", error))
+    })?;
+
+    self.bulk.push_str(&action.to_string());
+    self.bulk.push('\n');
+    self.bulk.push_str(&document);
+    self.bulk.push('\n');
+    self.pending += 1;
+
+    if self.pending >= BULK_SIZE {
+      self.flush()?;
+    }
+    Ok(())
+  }
+
+  fn flush(&mut self) -> Result<(), MessageError> {
+    if self.pending == 0 {
+      return Ok(());
+    }
+
+    let url = format!("{}/_bulk", self.parameters.url.trim_end_matches('/'));
+    let mut request = ureq::post(&url);
+    request.set("Content-Type", "application/x-ndjson");
+    if let Some(username) = &self.parameters.username {
+      request.auth(username, self.parameters.password.as_deref().unwrap_or(""));
+    }
+
+    let response = request.send_string(&self.bulk);
+    if let Some(error) = response.synthetic_error() {
+      return Err(MessageError::RuntimeError(format!(
+        "Unable to index the results into {}: {}",
+        url, error
+      )));
+    }
+    if response.error() {
+      return Err(MessageError::RuntimeError(format!(
+        "Unable to index the results into {}: {}",
+        url,
+        response.status_line()
+      )));
+    }
+
+    let body: serde_json::Value = response.into_json().map_err(|error| {
+      MessageError::RuntimeError(format!("Invalid response from {}: {}", url, error))
+    })?;
+    if body["errors"].as_bool() == Some(true) {
+      return Err(MessageError::RuntimeError(format!(
+        "Some results were rejected by {}",
+        url
+      )));
+    }
+
+    self.bulk.clear();
+    self.pending = 0;
+    Ok(())
+  }
+}
@@ -0,0 +1,87 @@
+use mcai_worker_sdk::{JsonSchema, MessageError};
+#[cfg(feature = "kafka")]
+use rdkafka::{
+  producer::{BaseProducer, BaseRecord},
+  ClientConfig,
+};
+#[cfg(feature = "kafka")]
+use std::time::Duration;
+
+/// Kafka topic receiving the results, in addition to the worker channel
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct KafkaParameters {
+  /// Comma separated list of the brokers, like localhost:9092
+  brokers: String,
+  /// Topic receiving the results, keyed by job identifier
+  topic: String,
+}
+
+/// Publish the results to a Kafka topic
+pub struct KafkaSink {
+  #[cfg(feature = "kafka")]
+  topic: String,
+  #[cfg(feature = "kafka")]
+  producer: BaseProducer,
+}
+
+impl std::fmt::Debug for KafkaSink {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    f.debug_struct("KafkaSink").finish()
+  }
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+  pub fn new(parameters: KafkaParameters) -> Result<Self, MessageError> {
+    let producer = ClientConfig::new()
+      .set("bootstrap.servers", &parameters.brokers)
+      .create()
+      .map_err(|error| {
+        MessageError::RuntimeError(format!(
+          "Unable to connect to Kafka brokers {}: {}",
+          parameters.brokers, error
+        ))
+      })?;
+
+    Ok(KafkaSink {
+      topic: parameters.topic,
+      producer,
+    })
+  }
+
+  pub fn publish(&self, job_id: u64, payload: &str) -> Result<(), MessageError> {
+    let key = job_id.to_string();
+    self
+      .producer
+      .send(BaseRecord::to(&self.topic).key(&key).payload(payload))
+      .map_err(|(error, _record)| {
+        MessageError::RuntimeError(format!(
+          "Unable to publish to Kafka topic {}: {}",
+          self.topic, error
+        ))
+      })?;
+    self.producer.poll(Duration::from_millis(0));
+    Ok(())
+  }
+
+  /// Wait for the queued messages to be delivered
+  pub fn flush(&self) {
+    self.producer.flush(Duration::from_secs(30));
+  }
+}
+
+#[cfg(not(feature = "kafka"))]
+impl KafkaSink {
+  pub fn new(parameters: KafkaParameters) -> Result<Self, MessageError> {
+    Err(MessageError::ParameterValueError(format!(
+      "Unable to publish to Kafka topic {} on {}: the worker is built without the kafka feature",
+      parameters.topic, parameters.brokers
+    )))
+  }
+
+  pub fn publish(&self, _job_id: u64, _payload: &str) -> Result<(), MessageError> {
+    Ok(())
+  }
+
+  pub fn flush(&self) {}
+}
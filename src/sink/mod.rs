@@ -0,0 +1,7 @@
+mod elasticsearch;
+mod kafka;
+mod webhook;
+
+pub use elasticsearch::{ElasticsearchParameters, ElasticsearchSink};
+pub use kafka::{KafkaParameters, KafkaSink};
+pub use webhook::{WebhookParameters, WebhookSink};
@@ -0,0 +1,124 @@
+use crate::RecognisedText;
+use hmac::{Hmac, Mac, NewMac};
+use mcai_worker_sdk::{warn, JsonSchema, MessageError};
+use sha2::Sha256;
+use std::thread;
+use std::time::Duration;
+
+/// HTTP endpoint notified with the results
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WebhookParameters {
+  /// URL receiving the results with POST requests
+  url: String,
+  /// Post each frame result, rather than a single document at the end of the job (default: false)
+  per_frame: Option<bool>,
+  /// Secret signing the request bodies with HMAC-SHA256, in the X-Signature-256 header
+  secret: Option<String>,
+  /// Number of retries of a failed request (default: 3)
+  retries: Option<u32>,
+}
+
+/// Document posted at the end of the job
+#[derive(Serialize)]
+struct JobDocument<'a> {
+  job_id: u64,
+  source_path: &'a str,
+  results: &'a [RecognisedText],
+}
+
+/// Post the results to an HTTP endpoint
+#[derive(Debug)]
+pub struct WebhookSink {
+  parameters: WebhookParameters,
+}
+
+impl WebhookSink {
+  pub fn new(parameters: WebhookParameters) -> Self {
+    WebhookSink { parameters }
+  }
+
+  /// Whether only the final document is posted, requiring to keep all the frame results
+  pub fn posts_job(&self) -> bool {
+    !self.parameters.per_frame.unwrap_or(false)
+  }
+
+  pub fn notify_frame(&self, payload: &str) -> Result<(), MessageError> {
+    if self.posts_job() {
+      return Ok(());
+    }
+    self.post(payload)
+  }
+
+  pub fn notify_job(
+    &self,
+    job_id: u64,
+    source_path: &str,
+    results: &[RecognisedText],
+  ) -> Result<(), MessageError> {
+    if !self.posts_job() {
+      return Ok(());
+    }
+
+    let payload = serde_json::to_string(&JobDocument {
+      job_id,
+      source_path,
+      results,
+    })
+    .map_err(|error| {
+      MessageError::RuntimeError(format!("Unable to serialize the results: {}", error))
+    })?;
+    self.post(&payload)
+  }
+
+  /// Hexadecimal HMAC-SHA256 of the body
+  fn signature(secret: &str, payload: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_varkey(secret.as_bytes()).expect("HMAC accepts any key size");
+    mac.update(payload.as_bytes());
+    mac
+      .finalize()
+      .into_bytes()
+      .iter()
+      .map(|byte| format!("{:02x}", byte))
+      .collect()
+  }
+
+  /// Post the payload, retrying with an exponential backoff
+  fn post(&self, payload: &str) -> Result<(), MessageError> {
+    let retries = self.parameters.retries.unwrap_or(3);
+    let signature = self
+      .parameters
+      .secret
+      .as_ref()
+      .map(|secret| format!("sha256={}", Self::signature(secret, payload)));
+
+    let mut attempt = 0;
+    loop {
+      let mut request = ureq::post(&self.parameters.url);
+      request.set("Content-Type", "application/json");
+      if let Some(signature) = &signature {
+        request.set("X-Signature-256", signature);
+      }
+
+      let response = request.send_string(payload);
+      let error = match response.synthetic_error() {
+        Some(error) => error.to_string(),
+        None if response.error() => response.status_line().to_string(),
+        None => return Ok(()),
+      };
+
+      if attempt >= retries {
+        return Err(MessageError::RuntimeError(format!(
+          "Unable to notify {}: {}",
+          self.parameters.url, error
+        )));
+      }
+
+      warn!(
+        "Unable to notify {} ({}), retrying",
+        self.parameters.url, error
+      );
+      thread::sleep(Duration::from_secs(1 << attempt.min(6)));
+      attempt += 1;
+    }
+  }
+}
@@ -0,0 +1,286 @@
+use crate::ocr::FrameBuffer;
+
+/// Number of column bins of the row signatures compared to estimate the scroll
+const SIGNATURE_BINS: usize = 16;
+
+/// Stitched image of the scrolling frames, with the frame and time each row entered the frame
+#[derive(Debug)]
+pub struct Composite {
+  data: Vec<u8>,
+  width: i32,
+  bytes_per_pixel: i32,
+  rows: Vec<(u32, u64)>,
+  /// Cut at the maximum length while the scroll goes on in the next frames
+  continued: bool,
+}
+
+impl Composite {
+  fn new(buffer: &FrameBuffer) -> Self {
+    Composite {
+      data: Vec::with_capacity((buffer.width * buffer.bytes_per_pixel * buffer.height) as usize),
+      width: buffer.width,
+      bytes_per_pixel: buffer.bytes_per_pixel,
+      rows: vec![],
+      continued: false,
+    }
+  }
+
+  pub fn buffer(&self) -> FrameBuffer<'_> {
+    FrameBuffer {
+      data: &self.data,
+      width: self.width,
+      height: self.rows.len() as i32,
+      bytes_per_pixel: self.bytes_per_pixel,
+      bytes_per_line: self.width * self.bytes_per_pixel,
+    }
+  }
+
+  pub fn is_continued(&self) -> bool {
+    self.continued
+  }
+
+  /// Frame and time in milliseconds at which the row entered the frame
+  pub fn row(&self, row: u32) -> (u32, u64) {
+    let row = (row as usize).min(self.rows.len().saturating_sub(1));
+    self.rows.get(row).copied().unwrap_or_default()
+  }
+
+  fn push_rows(&mut self, buffer: &FrameBuffer, from: usize, frame: u32, timestamp_ms: u64) {
+    let row_size = (buffer.width * buffer.bytes_per_pixel) as usize;
+    for y in from..buffer.height as usize {
+      let offset = y * buffer.bytes_per_line as usize;
+      self
+        .data
+        .extend_from_slice(&buffer.data[offset..offset + row_size]);
+      self.rows.push((frame, timestamp_ms));
+    }
+  }
+
+  /// Composite starting at the row of this one
+  fn tail(&self, from: usize) -> Composite {
+    let from = from.min(self.rows.len());
+    let row_size = (self.width * self.bytes_per_pixel) as usize;
+    Composite {
+      data: self.data[from * row_size..].to_vec(),
+      width: self.width,
+      bytes_per_pixel: self.bytes_per_pixel,
+      rows: self.rows[from..].to_vec(),
+      continued: false,
+    }
+  }
+}
+
+/// Mean luminance of the column bins of each row
+fn row_signatures(buffer: &FrameBuffer) -> Vec<[f64; SIGNATURE_BINS]> {
+  let bytes_per_pixel = buffer.bytes_per_pixel as usize;
+  let width = buffer.width as usize;
+  (0..buffer.height as usize)
+    .map(|y| {
+      let line = &buffer.data[y * buffer.bytes_per_line as usize..];
+      let mut signature = [0.0; SIGNATURE_BINS];
+      for (bin, value) in signature.iter_mut().enumerate() {
+        let (start, end) = (
+          bin * width / SIGNATURE_BINS,
+          ((bin + 1) * width / SIGNATURE_BINS).max(bin * width / SIGNATURE_BINS + 1),
+        );
+        let sum: u32 = (start..end.min(width))
+          .map(|x| {
+            let pixel = &line[x * bytes_per_pixel..];
+            if bytes_per_pixel >= 3 {
+              (299 * pixel[0] as u32 + 587 * pixel[1] as u32 + 114 * pixel[2] as u32) / 1000
+            } else {
+              pixel[0] as u32
+            }
+          })
+          .sum();
+        *value = sum as f64 / (end - start) as f64;
+      }
+      signature
+    })
+    .collect()
+}
+
+/// Mean difference between the previous rows moved up by `scroll` and the current ones
+fn scroll_difference(
+  previous: &[[f64; SIGNATURE_BINS]],
+  current: &[[f64; SIGNATURE_BINS]],
+  scroll: usize,
+) -> f64 {
+  let rows = current.len().min(previous.len()).saturating_sub(scroll);
+  if rows == 0 {
+    return f64::MAX;
+  }
+  let sum: f64 = (0..rows)
+    .map(|y| {
+      previous[y + scroll]
+        .iter()
+        .zip(current[y].iter())
+        .map(|(previous, current)| (previous - current).abs())
+        .sum::<f64>()
+    })
+    .sum();
+  sum / (rows * SIGNATURE_BINS) as f64
+}
+
+/// Stitcher of the frames scrolling up, estimating the scroll between them
+#[derive(Debug)]
+pub struct Stitcher {
+  max_scroll: Option<usize>,
+  max_difference: f64,
+  max_length: usize,
+  previous: Option<Vec<[f64; SIGNATURE_BINS]>>,
+  scroll: usize,
+  composite: Option<Composite>,
+}
+
+impl Stitcher {
+  /// Stitcher searching scrolls up to `max_scroll` rows (default: half of the frame height),
+  /// cutting the composites at `max_length` rows
+  pub fn new(max_scroll: Option<u32>, max_difference: f64, max_length: u32) -> Self {
+    Stitcher {
+      max_scroll: max_scroll.map(|max_scroll| max_scroll as usize),
+      max_difference,
+      max_length: max_length as usize,
+      previous: None,
+      scroll: 0,
+      composite: None,
+    }
+  }
+
+  /// Stitch the frame below the previous ones, returning the composite to recognise when it is
+  /// complete: the frame starts another shot, or the composite reaches the maximum length,
+  /// the next one then starting with its last frame
+  pub fn add(&mut self, buffer: &FrameBuffer, frame: u32, timestamp_ms: u64) -> Option<Composite> {
+    let signatures = row_signatures(buffer);
+    let height = signatures.len();
+
+    let scroll = self.previous.as_ref().and_then(|previous| {
+      if previous.len() != height {
+        return None;
+      }
+      let max_scroll = self.max_scroll.unwrap_or(height / 2).min(height - 1);
+      // start with the previous scroll, the rolls moving at a constant speed
+      std::iter::once(self.scroll.min(max_scroll))
+        .chain(0..=max_scroll)
+        .map(|scroll| (scroll, scroll_difference(previous, &signatures, scroll)))
+        .fold(
+          None,
+          |best: Option<(usize, f64)>, (scroll, difference)| match best {
+            Some((_, best_difference)) if best_difference <= difference => best,
+            _ => Some((scroll, difference)),
+          },
+        )
+        .filter(|(_, difference)| *difference <= self.max_difference)
+        .map(|(scroll, _)| scroll)
+    });
+    self.previous = Some(signatures);
+
+    if let (Some(scroll), Some(composite)) = (scroll, &mut self.composite) {
+      self.scroll = scroll;
+      composite.push_rows(buffer, height - scroll, frame, timestamp_ms);
+
+      if composite.rows.len() < self.max_length.max(2 * height) {
+        return None;
+      }
+      let tail = composite.tail(composite.rows.len() - height);
+      let mut completed = self.composite.replace(tail)?;
+      completed.continued = true;
+      return Some(completed);
+    }
+
+    // new roll, or another shot
+    self.scroll = 0;
+    let mut composite = Composite::new(buffer);
+    composite.push_rows(buffer, 0, frame, timestamp_ms);
+    self.composite.replace(composite)
+  }
+
+  /// Start the composite following the continued one just returned at its row instead of its
+  /// last frame, to read again the text cut at its end
+  pub fn resume_from(&mut self, completed: &Composite, row: u32) {
+    if completed.continued && row > 0 {
+      self.composite = Some(completed.tail(row as usize));
+    }
+  }
+
+  /// Composite of the frames not recognised yet, at the end of the job
+  pub fn finish(&mut self) -> Option<Composite> {
+    self.previous = None;
+    self.composite.take()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const WIDTH: usize = 16;
+  const HEIGHT: usize = 8;
+
+  /// Gray rows of a roll, each one with its own pattern
+  fn roll(rows: usize) -> Vec<u8> {
+    (0..rows)
+      .flat_map(|y| (0..WIDTH).map(move |x| ((y * 37 + x * 13 * (y % 7 + 1)) % 251) as u8))
+      .collect()
+  }
+
+  fn frame(data: &[u8]) -> FrameBuffer<'_> {
+    FrameBuffer {
+      data,
+      width: WIDTH as i32,
+      height: (data.len() / WIDTH) as i32,
+      bytes_per_pixel: 1,
+      bytes_per_line: WIDTH as i32,
+    }
+  }
+
+  /// Frame of the roll scrolled up by `scroll` rows
+  fn window(roll: &[u8], scroll: usize) -> &[u8] {
+    &roll[scroll * WIDTH..(scroll + HEIGHT) * WIDTH]
+  }
+
+  #[test]
+  fn scrolling_frames_are_stitched() {
+    let roll = roll(20);
+    let mut stitcher = Stitcher::new(None, 1.0, 100);
+
+    for (frame_index, scroll) in [0, 3, 6].iter().enumerate() {
+      let frame_index = frame_index as u32;
+      let added = stitcher.add(
+        &frame(window(&roll, *scroll)),
+        frame_index,
+        frame_index as u64 * 40,
+      );
+      assert!(added.is_none());
+    }
+    // another shot completes the roll
+    let other_shot = vec![0; WIDTH * HEIGHT];
+    let composite = stitcher.add(&frame(&other_shot), 3, 120).unwrap();
+
+    assert!(!composite.is_continued());
+    assert_eq!(composite.buffer().height, 14);
+    assert_eq!(composite.buffer().data, &roll[..14 * WIDTH]);
+    assert_eq!(composite.row(7), (0, 0));
+    assert_eq!(composite.row(9), (1, 40));
+    assert_eq!(composite.row(13), (2, 80));
+    assert_eq!(composite.row(100), (2, 80));
+    assert_eq!(stitcher.finish().unwrap().buffer().data, &other_shot[..]);
+  }
+
+  #[test]
+  fn long_rolls_are_cut_at_the_maximum_length() {
+    let roll = roll(20);
+    let mut stitcher = Stitcher::new(None, 1.0, 16);
+
+    let mut completed = None;
+    for (frame_index, scroll) in [0, 3, 6, 9].iter().enumerate() {
+      completed = stitcher.add(&frame(window(&roll, *scroll)), frame_index as u32, 0);
+    }
+    let completed = completed.unwrap();
+
+    assert!(completed.is_continued());
+    assert_eq!(completed.buffer().data, &roll[..17 * WIDTH]);
+    // the next composite starts with the last frame
+    assert_eq!(stitcher.finish().unwrap().buffer().data, window(&roll, 9));
+  }
+}
@@ -0,0 +1,120 @@
+use crate::region::Rectangle;
+use crate::timing::TimingStats;
+use crate::{RecognisedText, SCHEMA_VERSION};
+use std::time::Instant;
+
+/// Counters updated for each processed frame, to summarize the job
+#[derive(Debug, Default)]
+pub struct SummaryCounters {
+  started: Option<Instant>,
+  total_characters: u64,
+  confidence_sum: f64,
+  confident_frames: u32,
+  rejected_frames: u32,
+  alerts: u32,
+  timecode_discontinuities: u32,
+  unreadable_timecodes: u32,
+}
+
+impl SummaryCounters {
+  pub fn start(&mut self) {
+    *self = SummaryCounters {
+      started: Some(Instant::now()),
+      ..Default::default()
+    };
+  }
+
+  pub fn add(&mut self, result: &RecognisedText) {
+    self.total_characters += result
+      .text
+      .chars()
+      .filter(|character| !character.is_whitespace())
+      .count() as u64;
+    if let Some(confidence) = result.confidence {
+      self.confidence_sum += confidence as f64;
+      self.confident_frames += 1;
+    }
+  }
+
+  /// Count a watchlist alert
+  pub fn alert(&mut self) {
+    self.alerts += 1;
+  }
+
+  /// Count a discontinuity of the burned-in timecode
+  pub fn timecode_discontinuity(&mut self) {
+    self.timecode_discontinuities += 1;
+  }
+
+  /// Count a frame without readable burned-in timecode
+  pub fn unreadable_timecode(&mut self) {
+    self.unreadable_timecodes += 1;
+  }
+
+  /// Count a frame dropped for its low confidence
+  pub fn reject(&mut self) {
+    self.rejected_frames += 1;
+  }
+}
+
+/// Record describing the whole job, produced at the end of the process
+#[derive(Debug, Serialize)]
+pub struct JobSummary {
+  pub schema_version: &'static str,
+  pub job_id: u64,
+  pub frames_decoded: u32,
+  pub frames_processed: u32,
+  pub frames_skipped: u32,
+  /// Number of processed frames dropped by reject_below_confidence
+  pub frames_rejected: u32,
+  /// Number of recognised characters, excluding the whitespaces
+  pub total_characters: u64,
+  /// Mean confidence of the frames with text, between 0 and 100
+  pub mean_confidence: Option<f32>,
+  /// Number of watchlist alerts
+  pub alerts: u32,
+  /// Number of discontinuities of the burned-in timecode
+  pub timecode_discontinuities: u32,
+  /// Number of processed frames without readable burned-in timecode
+  pub unreadable_timecodes: u32,
+  pub wall_clock_ms: u64,
+  pub timings: TimingStats,
+  pub region: Option<Rectangle>,
+  pub parameters: serde_json::Value,
+}
+
+impl JobSummary {
+  pub fn new(
+    counters: &SummaryCounters,
+    job_id: u64,
+    frames_decoded: u32,
+    timings: &TimingStats,
+    region: Option<Rectangle>,
+    parameters: serde_json::Value,
+  ) -> Self {
+    JobSummary {
+      schema_version: SCHEMA_VERSION,
+      job_id,
+      frames_decoded,
+      frames_processed: timings.processed_frames,
+      frames_skipped: frames_decoded.saturating_sub(timings.processed_frames),
+      frames_rejected: counters.rejected_frames,
+      total_characters: counters.total_characters,
+      mean_confidence: if counters.confident_frames > 0 {
+        Some((counters.confidence_sum / counters.confident_frames as f64) as f32)
+      } else {
+        None
+      },
+      alerts: counters.alerts,
+      timecode_discontinuities: counters.timecode_discontinuities,
+      unreadable_timecodes: counters.unreadable_timecodes,
+      wall_clock_ms: counters
+        .started
+        .map(|started| started.elapsed().as_millis() as u64)
+        .unwrap_or(0),
+      timings: timings.clone(),
+      region,
+      parameters,
+    }
+  }
+}
@@ -0,0 +1,331 @@
+use crate::output::OutputContext;
+use crate::timecode::format_milliseconds;
+use crate::RecognisedText;
+
+/// Text displayed over consecutive processed frames
+#[derive(Clone, Debug, Serialize)]
+pub struct TextEvent {
+  pub text: String,
+  pub start_frame: u32,
+  pub end_frame: u32,
+  pub start_pts: u64,
+  pub end_pts: u64,
+  pub start_ms: u64,
+  pub end_ms: u64,
+  pub start_timecode: String,
+  pub end_timecode: String,
+}
+
+impl TextEvent {
+  /// Non-empty lines of the event text
+  pub fn lines(&self) -> impl Iterator<Item = &str> {
+    self.text.lines().filter(|line| !line.trim().is_empty())
+  }
+
+  /// Event text on a single line, for the marker names and comments
+  pub fn single_line(&self) -> String {
+    self
+      .lines()
+      .map(str::trim)
+      .collect::<Vec<&str>>()
+      .join(" / ")
+  }
+}
+
+/// Text compared between frames, ignoring the whitespace differences
+pub fn normalize(text: &str) -> String {
+  text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Number of single character edits needed to change one text into the other
+pub fn levenshtein_distance(left: &str, right: &str) -> usize {
+  let right: Vec<char> = right.chars().collect();
+  let mut previous: Vec<usize> = (0..=right.len()).collect();
+
+  for (i, left_char) in left.chars().enumerate() {
+    let mut current = vec![i + 1];
+    for (j, right_char) in right.iter().enumerate() {
+      let substitution = previous[j] + if left_char == *right_char { 0 } else { 1 };
+      current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+    }
+    previous = current;
+  }
+
+  previous[right.len()]
+}
+
+/// Frames of an event recognising the same text, ignoring the case and whitespace differences
+#[derive(Debug)]
+struct Vote {
+  key: String,
+  count: usize,
+  /// Spellings of the text, with the number of frames for each
+  spellings: Vec<(String, usize)>,
+}
+
+/// Texts recognised over the frames of an event
+#[derive(Debug, Default)]
+pub struct Votes(Vec<Vote>);
+
+/// First item with the highest count
+fn most_frequent<T>(items: &[T], count: impl Fn(&T) -> usize) -> Option<&T> {
+  let mut majority: Option<&T> = None;
+  for item in items {
+    if majority
+      .map(|best| count(item) > count(best))
+      .unwrap_or(true)
+    {
+      majority = Some(item);
+    }
+  }
+  majority
+}
+
+impl Votes {
+  pub fn add(&mut self, text: &str) {
+    let key = normalize(text).to_lowercase();
+    let vote = match self.0.iter().position(|vote| vote.key == key) {
+      Some(position) => &mut self.0[position],
+      None => {
+        self.0.push(Vote {
+          key,
+          count: 0,
+          spellings: vec![],
+        });
+        self.0.last_mut().unwrap()
+      }
+    };
+    vote.count += 1;
+    match vote
+      .spellings
+      .iter_mut()
+      .find(|(spelling, _)| spelling == text)
+    {
+      Some((_, count)) => *count += 1,
+      None => vote.spellings.push((text.to_string(), 1)),
+    }
+  }
+
+  /// Most frequent text, in its most frequent spelling, the first recognised one on ties
+  pub fn majority(&self) -> Option<&str> {
+    most_frequent(&self.0, |vote| vote.count)
+      .and_then(|vote| most_frequent(&vote.spellings, |(_, count)| *count))
+      .map(|(text, _)| text.as_str())
+  }
+}
+
+/// Whether the next result directly follows the result, which lasts until it.
+/// The listed frames only last until the next decoded frame, and the frames of a segment do not
+/// last into the next segment.
+fn is_followed_by(result: &RecognisedText, next: &RecognisedText, context: &OutputContext) -> bool {
+  if context.listed_frames && next.frame != result.frame + 1 {
+    return false;
+  }
+  match &context.segments {
+    Some(segments) => segments.index(result.timestamp_ms) == segments.index(next.timestamp_ms),
+    None => true,
+  }
+}
+
+/// Merge consecutive frames sharing the same recognised text into events.
+/// Each frame lasts until the next processed frame when it directly follows, or else until
+/// its own end, like the last one.
+/// Texts within `merge_distance` edits of the previous frame are merged too,
+/// the event keeping the text recognised on most of its frames.
+pub fn group_text_events(results: &[RecognisedText], context: &OutputContext) -> Vec<TextEvent> {
+  let mut events: Vec<TextEvent> = vec![];
+  let mut votes: Vec<Votes> = vec![];
+  let mut current: Option<String> = None;
+
+  for (index, result) in results.iter().enumerate() {
+    // an event does not span the frames which are not recognised
+    if index > 0 && !is_followed_by(&results[index - 1], result, context) {
+      current = None;
+    }
+    let text = normalize(&result.text);
+    let end_pts = results
+      .get(index + 1)
+      .filter(|next| is_followed_by(result, next, context))
+      .map(|next| next.pts)
+      .unwrap_or(result.pts + result.duration);
+
+    let merged = match &current {
+      Some(previous) => {
+        !text.is_empty() && levenshtein_distance(previous, &text) <= context.merge_distance
+      }
+      None => false,
+    };
+    if merged {
+      if let (Some(event), Some(event_votes)) = (events.last_mut(), votes.last_mut()) {
+        event.end_frame = result.frame;
+        event.end_pts = end_pts;
+        event_votes.add(result.text.trim());
+        current = Some(text);
+        continue;
+      }
+    }
+
+    current = if text.is_empty() { None } else { Some(text) };
+    if current.is_some() {
+      let mut event_votes = Votes::default();
+      event_votes.add(result.text.trim());
+      votes.push(event_votes);
+      events.push(TextEvent {
+        text: result.text.trim().to_string(),
+        start_frame: result.frame,
+        end_frame: result.frame,
+        start_pts: result.pts,
+        end_pts,
+        start_ms: 0,
+        end_ms: 0,
+        start_timecode: String::new(),
+        end_timecode: String::new(),
+      });
+    }
+  }
+
+  for (event, event_votes) in events.iter_mut().zip(votes.iter()) {
+    if let Some(text) = event_votes.majority() {
+      event.text = text.to_string();
+    }
+    event.start_ms = context.time_base.to_milliseconds(event.start_pts);
+    event.end_ms = context.time_base.to_milliseconds(event.end_pts);
+    event.start_timecode = format_milliseconds(event.start_ms, '.');
+    event.end_timecode = format_milliseconds(event.end_ms, '.');
+  }
+
+  events
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sampling::{Segment, Segments};
+
+  /// Result of a frame of a 25 fps stream, timed in milliseconds
+  fn result(frame: u32, text: &str) -> RecognisedText {
+    RecognisedText {
+      frame,
+      pts: frame as u64 * 40,
+      timestamp_ms: frame as u64 * 40,
+      duration: 40,
+      text: text.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn levenshtein_distances() {
+    assert_eq!(levenshtein_distance("", ""), 0);
+    assert_eq!(levenshtein_distance("NEWS", ""), 4);
+    assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
+    assert_eq!(levenshtein_distance("BREAKING", "8REAKING"), 1);
+    assert_eq!(levenshtein_distance("Été", "Eté"), 1);
+  }
+
+  #[test]
+  fn normalized_whitespace() {
+    assert_eq!(normalize("  BREAKING \n NEWS\t"), "BREAKING NEWS");
+  }
+
+  #[test]
+  fn close_texts_are_merged_within_the_merge_distance() {
+    let results = [
+      result(0, "BREAKING NEWS"),
+      result(1, "8REAKING NEWS"),
+      result(2, "BREAKING NEWS"),
+      result(3, ""),
+      result(4, "WEATHER"),
+    ];
+    let context = OutputContext {
+      merge_distance: 1,
+      ..Default::default()
+    };
+    let events = group_text_events(&results, &context);
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].text, "BREAKING NEWS");
+    assert_eq!((events[0].start_frame, events[0].end_frame), (0, 2));
+    assert_eq!((events[0].start_ms, events[0].end_ms), (0, 120));
+    assert_eq!(events[0].end_timecode, "00:00:00.120");
+    assert_eq!(events[1].text, "WEATHER");
+
+    let events = group_text_events(&results, &OutputContext::default());
+    assert_eq!(events.len(), 4);
+  }
+
+  #[test]
+  fn sampled_frames_last_until_the_next_one() {
+    let results = [result(0, "NEWS"), result(10, "NEWS"), result(20, "SPORT")];
+    let events = group_text_events(&results, &OutputContext::default());
+
+    assert_eq!(events.len(), 2);
+    assert_eq!((events[0].start_ms, events[0].end_ms), (0, 800));
+    assert_eq!((events[1].start_ms, events[1].end_ms), (800, 840));
+  }
+
+  #[test]
+  fn segments_end_their_text_events() {
+    let segments: Vec<Segment> = serde_json::from_value(serde_json::json!([
+      { "start": 0, "end": 1000, "label": "intro" },
+      { "start": 1000, "end": 2000 },
+      { "start": 5000, "end": 6000 },
+    ]))
+    .unwrap();
+    let context = OutputContext {
+      segments: Some(Segments::new(&segments).unwrap()),
+      ..Default::default()
+    };
+    let results = [
+      result(0, "NEWS"),
+      result(10, "NEWS"),
+      result(20, "NEWS"),
+      result(30, "NEWS"),
+      result(40, "NEWS"),
+      result(130, "NEWS"),
+    ];
+    let events = group_text_events(&results, &context);
+
+    assert_eq!(events.len(), 3);
+    assert_eq!((events[0].start_ms, events[0].end_ms), (0, 840));
+    assert_eq!((events[1].start_ms, events[1].end_ms), (1200, 1640));
+    assert_eq!((events[2].start_ms, events[2].end_ms), (5200, 5240));
+  }
+
+  #[test]
+  fn listed_frames_last_until_their_own_end() {
+    let context = OutputContext {
+      listed_frames: true,
+      ..Default::default()
+    };
+    let results = [result(10, "NEWS"), result(11, "NEWS"), result(50, "NEWS")];
+    let events = group_text_events(&results, &context);
+
+    assert_eq!(events.len(), 2);
+    assert_eq!((events[0].start_frame, events[0].end_frame), (10, 11));
+    assert_eq!((events[0].start_ms, events[0].end_ms), (400, 480));
+    assert_eq!((events[1].start_ms, events[1].end_ms), (2000, 2040));
+  }
+
+  #[test]
+  fn votes_count_the_case_and_whitespace_variants_together() {
+    let mut votes = Votes::default();
+    votes.add("BREAKING NEWS");
+    votes.add("Breaking  News");
+    votes.add("BREAKING NEWS");
+    votes.add("8REAKING NEWS");
+    votes.add("8REAKING NEWS");
+
+    assert_eq!(votes.majority(), Some("BREAKING NEWS"));
+  }
+
+  #[test]
+  fn votes_keep_the_first_text_on_ties() {
+    let mut votes = Votes::default();
+    assert_eq!(votes.majority(), None);
+    votes.add("first");
+    votes.add("second");
+
+    assert_eq!(votes.majority(), Some("first"));
+  }
+}
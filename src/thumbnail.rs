@@ -0,0 +1,62 @@
+use crate::layout::{Line, Word};
+use crate::ocr::FrameBuffer;
+use crate::region::Rectangle;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use std::fs;
+use std::path::Path;
+
+/// Level of the text regions saved as thumbnails
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailLevel {
+  Word,
+  Line,
+}
+
+impl Default for ThumbnailLevel {
+  fn default() -> Self {
+    ThumbnailLevel::Line
+  }
+}
+
+/// PNG crops of the recognised text, for a visual review of the results
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ThumbnailParameters {
+  /// Directory receiving the crops, named by frame and region index
+  directory: String,
+  /// Level of the cropped regions: word or line (default: line)
+  level: Option<ThumbnailLevel>,
+}
+
+impl ThumbnailParameters {
+  pub fn create_directory(&self) -> Result<(), MessageError> {
+    fs::create_dir_all(&self.directory).map_err(|error| {
+      MessageError::RuntimeError(format!("Unable to create {}: {}", self.directory, error))
+    })
+  }
+}
+
+/// Save a crop of each word or line recognised in the processed frame
+pub fn save_thumbnails(
+  parameters: &ThumbnailParameters,
+  buffer: &FrameBuffer,
+  frame: u32,
+  words: &[Word],
+  lines: &[Line],
+) -> Result<(), MessageError> {
+  let level = parameters.level.unwrap_or_default();
+  let regions: Vec<&Rectangle> = match level {
+    ThumbnailLevel::Word => words.iter().map(|word| &word.bounding_box).collect(),
+    ThumbnailLevel::Line => lines.iter().map(|line| &line.bounding_box).collect(),
+  };
+  let level_name = match level {
+    ThumbnailLevel::Word => "word",
+    ThumbnailLevel::Line => "line",
+  };
+
+  for (index, region) in regions.into_iter().enumerate() {
+    let file_name = format!("frame-{:06}-{}-{:03}.png", frame, level_name, index);
+    buffer.save_png(&Path::new(&parameters.directory).join(file_name), region)?;
+  }
+  Ok(())
+}
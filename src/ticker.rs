@@ -0,0 +1,112 @@
+use crate::layout::Word;
+use crate::ocr::{FrameBuffer, OwnedFrame};
+use crate::stitching::{Composite, Stitcher};
+use crate::timecode::format_milliseconds;
+use mcai_worker_sdk::JsonSchema;
+
+/// Words separating the headlines of a ticker, like bullets
+const SEPARATORS: [&str; 9] = ["•", "|", "◆", "◇", "●", "■", "▪", "★", "///"];
+
+/// Stitching of the horizontal crawl of a news ticker into a strip recognised at once,
+/// the region of interest being set on the ticker
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct TickerParameters {
+  /// Maximum crawl between two processed frames, in pixels (default: half of the frame width)
+  max_scroll: Option<u32>,
+  /// Mean difference of the column luminance above which the frames are not part of the same
+  /// crawl, from 0 to 255 (default: 24)
+  max_difference: Option<f64>,
+  /// Width of the stitched strip recognised before stitching the next one, in pixels
+  /// (default: 16000)
+  max_width: Option<u32>,
+}
+
+/// Sentence read in the crawl, with the time its start entered the frame
+#[derive(Debug, Serialize)]
+pub struct Headline {
+  pub text: String,
+  pub frame: u32,
+  pub timestamp_ms: u64,
+  /// Approximate timecode, the crawl being estimated between the processed frames
+  pub timecode: String,
+}
+
+/// Stitcher of the crawl, the frames being turned to scroll up
+#[derive(Debug)]
+pub struct Ticker {
+  stitcher: Stitcher,
+}
+
+impl Ticker {
+  pub fn new(parameters: &TickerParameters) -> Self {
+    Ticker {
+      stitcher: Stitcher::new(
+        parameters.max_scroll,
+        parameters.max_difference.unwrap_or(24.0),
+        parameters.max_width.unwrap_or(16000),
+      ),
+    }
+  }
+
+  /// Stitch the frame right of the previous ones,
+  /// returning the stitched crawl to recognise when it is complete
+  pub fn add(&mut self, buffer: &FrameBuffer, frame: u32, timestamp_ms: u64) -> Option<Composite> {
+    // the text crawling left scrolls up once turned clockwise
+    let turned = buffer.rotated(90);
+    self.stitcher.add(&turned.buffer(), frame, timestamp_ms)
+  }
+
+  /// Stitched crawl of the frames not recognised yet, at the end of the job
+  pub fn finish(&mut self) -> Option<Composite> {
+    self.stitcher.finish()
+  }
+
+  /// Strip of the stitched crawl, with the text upright
+  pub fn strip(composite: &Composite) -> OwnedFrame {
+    composite.buffer().rotated(270)
+  }
+
+  /// Headlines of the words recognised in the strip, the last one being read again with the
+  /// next strip when the crawl goes on
+  pub fn headlines(&mut self, composite: &Composite, words: &[Word]) -> Vec<Headline> {
+    let mut sentences: Vec<(u32, Vec<&str>)> = vec![];
+    let mut ended = true;
+    for word in words {
+      let text = word.text.trim();
+      if text.is_empty() {
+        continue;
+      }
+      if SEPARATORS.contains(&text) {
+        ended = true;
+        continue;
+      }
+      if ended {
+        sentences.push((word.bounding_box.left, vec![]));
+      }
+      if let Some((_, sentence)) = sentences.last_mut() {
+        sentence.push(text);
+      }
+      ended = text.ends_with(['.', '!', '?']);
+    }
+
+    // the columns of the strip are the rows of the stitched crawl
+    if composite.is_continued() && sentences.len() > 1 {
+      if let Some((left, _)) = sentences.pop() {
+        self.stitcher.resume_from(composite, left);
+      }
+    }
+
+    sentences
+      .into_iter()
+      .map(|(left, sentence)| {
+        let (frame, timestamp_ms) = composite.row(left);
+        Headline {
+          text: sentence.join(" "),
+          frame,
+          timestamp_ms,
+          timecode: format_milliseconds(timestamp_ms, '.'),
+        }
+      })
+      .collect()
+  }
+}
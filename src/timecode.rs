@@ -0,0 +1,202 @@
+use stainless_ffmpeg_sys::AVRational;
+
+/// Time base of the processed video stream, used to convert frame PTS into time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeBase {
+  pub num: i32,
+  pub den: i32,
+}
+
+impl Default for TimeBase {
+  fn default() -> Self {
+    TimeBase { num: 1, den: 1000 }
+  }
+}
+
+impl From<AVRational> for TimeBase {
+  fn from(rational: AVRational) -> Self {
+    TimeBase {
+      num: rational.num,
+      den: rational.den,
+    }
+  }
+}
+
+impl TimeBase {
+  pub fn to_milliseconds(self, pts: u64) -> u64 {
+    if self.den == 0 {
+      return 0;
+    }
+    (pts as u128 * self.num as u128 * 1000 / self.den as u128) as u64
+  }
+}
+
+/// Format milliseconds as `HH:MM:SS<separator>mmm`
+pub fn format_milliseconds(milliseconds: u64, separator: char) -> String {
+  let hours = milliseconds / 3_600_000;
+  let minutes = (milliseconds / 60_000) % 60;
+  let seconds = (milliseconds / 1000) % 60;
+  let millis = milliseconds % 1000;
+
+  format!(
+    "{:02}:{:02}:{:02}{}{:03}",
+    hours, minutes, seconds, separator, millis
+  )
+}
+
+/// Civil date (year, month, day) from the number of days since 1970-01-01
+pub fn civil_date(days: i64) -> (i64, i64, i64) {
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let day_of_era = z - era * 146_097;
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let mp = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+  (year, month, day)
+}
+
+/// SMPTE-like timecode, counting frames at an integer frame rate
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timecode {
+  pub hours: u32,
+  pub minutes: u32,
+  pub seconds: u32,
+  pub frames: u32,
+}
+
+impl Timecode {
+  pub fn from_milliseconds(milliseconds: u64, frame_rate: u32) -> Self {
+    Timecode {
+      hours: (milliseconds / 3_600_000) as u32,
+      minutes: ((milliseconds / 60_000) % 60) as u32,
+      seconds: ((milliseconds / 1000) % 60) as u32,
+      frames: ((milliseconds % 1000) * frame_rate as u64 / 1000) as u32,
+    }
+  }
+
+  /// Number of frames since 00:00:00:00, skipping the frame numbers dropped by the drop-frame timecodes
+  pub fn to_frames(self, frame_rate: u32, drop_frame: bool) -> i64 {
+    let total_minutes = (self.hours * 60 + self.minutes) as i64;
+    let frames =
+      (total_minutes * 60 + self.seconds as i64) * frame_rate as i64 + self.frames as i64;
+    if drop_frame {
+      frames - dropped_frames(frame_rate) * (total_minutes - total_minutes / 10)
+    } else {
+      frames
+    }
+  }
+
+  pub fn from_frames(frames: i64, frame_rate: u32, drop_frame: bool) -> Self {
+    let frame_rate = frame_rate.max(1) as i64;
+    let mut frames = frames.max(0);
+    if drop_frame {
+      let dropped = dropped_frames(frame_rate as u32);
+      let frames_per_minute = frame_rate * 60 - dropped;
+      let frames_per_ten_minutes = frames_per_minute * 10 + dropped;
+      let ten_minutes = frames / frames_per_ten_minutes;
+      let remainder = frames % frames_per_ten_minutes;
+      frames += dropped * 9 * ten_minutes;
+      if remainder > dropped {
+        frames += dropped * ((remainder - dropped) / frames_per_minute);
+      }
+    }
+
+    Timecode {
+      hours: (frames / (frame_rate * 3600)) as u32,
+      minutes: ((frames / (frame_rate * 60)) % 60) as u32,
+      seconds: ((frames / frame_rate) % 60) as u32,
+      frames: (frames % frame_rate) as u32,
+    }
+  }
+}
+
+/// Frame numbers dropped at the start of each minute, except every tenth, by the drop-frame timecodes
+fn dropped_frames(frame_rate: u32) -> i64 {
+  (frame_rate as i64 / 30) * 2
+}
+
+impl std::fmt::Display for Timecode {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(
+      f,
+      "{:02}:{:02}:{:02}:{:02}",
+      self.hours, self.minutes, self.seconds, self.frames
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn timecode(hours: u32, minutes: u32, seconds: u32, frames: u32) -> Timecode {
+    Timecode {
+      hours,
+      minutes,
+      seconds,
+      frames,
+    }
+  }
+
+  #[test]
+  fn drop_frame_timecodes_skip_the_first_frames_of_the_minutes() {
+    // 29.97 fps, the frames 00 and 01 of each minute being dropped except every tenth minute
+    assert_eq!(timecode(0, 0, 59, 29).to_frames(30, true), 1799);
+    assert_eq!(timecode(0, 1, 0, 2).to_frames(30, true), 1800);
+    assert_eq!(timecode(0, 10, 0, 0).to_frames(30, true), 17_982);
+    assert_eq!(timecode(1, 0, 0, 0).to_frames(30, true), 107_892);
+
+    assert_eq!(
+      Timecode::from_frames(1799, 30, true),
+      timecode(0, 0, 59, 29)
+    );
+    assert_eq!(Timecode::from_frames(1800, 30, true), timecode(0, 1, 0, 2));
+    assert_eq!(
+      Timecode::from_frames(17_982, 30, true),
+      timecode(0, 10, 0, 0)
+    );
+    assert_eq!(
+      Timecode::from_frames(107_892, 30, true),
+      timecode(1, 0, 0, 0)
+    );
+  }
+
+  #[test]
+  fn frames_round_trip() {
+    for frames in (0..200_000).step_by(7) {
+      for &(frame_rate, drop_frame) in &[(25, false), (30, true), (60, true)] {
+        let timecode = Timecode::from_frames(frames, frame_rate, drop_frame);
+        assert_eq!(timecode.to_frames(frame_rate, drop_frame), frames);
+      }
+    }
+  }
+
+  #[test]
+  fn format_timecodes() {
+    assert_eq!(
+      Timecode::from_milliseconds(3_723_480, 25).to_string(),
+      "01:02:03:12"
+    );
+    assert_eq!(format_milliseconds(3_723_480, ','), "01:02:03,480");
+    assert_eq!(
+      TimeBase {
+        num: 1,
+        den: 90_000
+      }
+      .to_milliseconds(180_000),
+      2000
+    );
+  }
+
+  #[test]
+  fn civil_dates() {
+    assert_eq!(civil_date(0), (1970, 1, 1));
+    assert_eq!(civil_date(11_016), (2000, 2, 29));
+    assert_eq!(civil_date(-1), (1969, 12, 31));
+  }
+}
@@ -0,0 +1,202 @@
+use crate::timecode::Timecode;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use regex::Regex;
+use stainless_ffmpeg_sys::{av_dict_get, AVDictionary, AVFormatContext, AVStream};
+use std::ffi::{CStr, CString};
+
+/// Characters of the burned-in timecodes
+pub const TIMECODE_CHARACTERS: &str = "0123456789:;.";
+/// Timecode like 10:00:00:00, the semicolon before the frames marking the drop-frame ones
+const TIMECODE_EXPRESSION: &str = r"(\d{2})[:;.](\d{2})[:;.](\d{2})([:;.])(\d{2})";
+
+/// Verification of the timecode burned in the frames, read in the region of interest
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct BurnedInTimecodeParameters {
+  /// Timecode of the first frame, like 10:00:00:00
+  /// (default: timecode of the container, or 00:00:00:00 when missing)
+  start_timecode: Option<String>,
+  /// Accepted difference between the burned-in and the expected timecodes, in frames (default: 0)
+  tolerance: Option<u32>,
+}
+
+/// Burned-in timecode leaving the expected sequence
+#[derive(Debug, Serialize)]
+pub struct TimecodeDiscontinuity {
+  pub job_id: u64,
+  pub frame: u32,
+  pub pts: u64,
+  pub timestamp_ms: u64,
+  /// Timecode of the frame following the container timecode
+  pub expected: String,
+  pub burned_in: String,
+  /// Last burned-in timecode read before the discontinuity
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub previous: Option<String>,
+  /// Difference between the burned-in and the expected timecodes, in frames
+  pub offset_frames: i64,
+}
+
+/// Burned-in timecode read on a frame, compared with the expected one
+#[derive(Debug)]
+pub struct TimecodeReading {
+  pub burned_in: Timecode,
+  pub discontinuity: Option<TimecodeDiscontinuity>,
+}
+
+/// Reader of the burned-in timecodes, following their offset from the container timecode
+#[derive(Debug)]
+pub struct TimecodeChecker {
+  regex: Regex,
+  start: Option<(Timecode, bool)>,
+  tolerance: i64,
+  frame_rate: f64,
+  timecode_rate: u32,
+  start_ms: u64,
+  /// Last burned-in timecode read, with its offset
+  previous: Option<(Timecode, i64)>,
+}
+
+/// Timecode of the text, like 10:00:00:00, with whether it is drop-frame like 10:00:00;00
+fn parse_timecode(regex: &Regex, text: &str) -> Option<(Timecode, bool)> {
+  let captures = regex.captures(text)?;
+  let number = |index: usize| captures[index].parse::<u32>().ok();
+
+  Some((
+    Timecode {
+      hours: number(1)?,
+      minutes: number(2)?,
+      seconds: number(3)?,
+      frames: number(5)?,
+    },
+    &captures[4] == ";",
+  ))
+}
+
+unsafe fn dictionary_value(dictionary: *mut AVDictionary, key: &str) -> Option<String> {
+  let key = CString::new(key).ok()?;
+  let entry = av_dict_get(dictionary, key.as_ptr(), std::ptr::null(), 0);
+  if entry.is_null() || (*entry).value.is_null() {
+    return None;
+  }
+  Some(CStr::from_ptr((*entry).value).to_string_lossy().to_string())
+}
+
+/// Timecode tag of the stream, or of the container
+///
+/// # Safety
+/// The pointers must reference an opened format context and one of its streams.
+pub unsafe fn container_timecode(
+  format_context: *mut AVFormatContext,
+  stream: *mut AVStream,
+) -> Option<String> {
+  dictionary_value((*stream).metadata, "timecode")
+    .or_else(|| dictionary_value((*format_context).metadata, "timecode"))
+}
+
+impl TimecodeChecker {
+  /// Checker of the stream, with the timecode of its container and its time of the first frame
+  pub fn new(
+    parameters: &BurnedInTimecodeParameters,
+    container_timecode: Option<&str>,
+    frame_rate: f64,
+    timecode_rate: u32,
+    start_ms: u64,
+  ) -> Result<Self, MessageError> {
+    let regex = Regex::new(TIMECODE_EXPRESSION).expect("valid timecode expression");
+    let start = match &parameters.start_timecode {
+      Some(start_timecode) => Some(parse_timecode(&regex, start_timecode).ok_or_else(|| {
+        MessageError::ParameterValueError(format!("Invalid start timecode {}", start_timecode))
+      })?),
+      None => container_timecode.and_then(|timecode| parse_timecode(&regex, timecode)),
+    };
+
+    Ok(TimecodeChecker {
+      regex,
+      start,
+      tolerance: parameters.tolerance.unwrap_or(0) as i64,
+      frame_rate,
+      timecode_rate,
+      start_ms,
+      previous: None,
+    })
+  }
+
+  /// Read the burned-in timecode of the frame text, None when it is unreadable
+  pub fn check(
+    &mut self,
+    text: &str,
+    job_id: u64,
+    frame: u32,
+    pts: u64,
+    timestamp_ms: u64,
+  ) -> Option<TimecodeReading> {
+    let (burned_in, burned_in_drop_frame) = parse_timecode(&self.regex, text)?;
+    let (start, drop_frame) = self.start.unwrap_or((
+      Timecode::from_frames(0, self.timecode_rate, false),
+      burned_in_drop_frame,
+    ));
+
+    let elapsed_frames =
+      (timestamp_ms.saturating_sub(self.start_ms) as f64 * self.frame_rate / 1000.0).round() as i64;
+    let expected_frames = start.to_frames(self.timecode_rate, drop_frame) + elapsed_frames;
+    let offset = burned_in.to_frames(self.timecode_rate, drop_frame) - expected_frames;
+
+    // compare with the previous offset, or with the start timecode on the first reading
+    let previous_offset = match (self.previous, self.start) {
+      (Some((_, previous_offset)), _) => previous_offset,
+      (None, Some(_)) => 0,
+      (None, None) => offset,
+    };
+    let discontinuity = if (offset - previous_offset).abs() > self.tolerance {
+      Some(TimecodeDiscontinuity {
+        job_id,
+        frame,
+        pts,
+        timestamp_ms,
+        expected: Timecode::from_frames(expected_frames, self.timecode_rate, drop_frame)
+          .to_string(),
+        burned_in: burned_in.to_string(),
+        previous: self.previous.map(|(timecode, _)| timecode.to_string()),
+        offset_frames: offset,
+      })
+    } else {
+      None
+    };
+
+    self.previous = Some((burned_in, offset));
+    Some(TimecodeReading {
+      burned_in,
+      discontinuity,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_burned_in_timecodes() {
+    let regex = Regex::new(TIMECODE_EXPRESSION).unwrap();
+    let timecode = |hours, minutes, seconds, frames| Timecode {
+      hours,
+      minutes,
+      seconds,
+      frames,
+    };
+
+    assert_eq!(
+      parse_timecode(&regex, "TC 10:02:03:04"),
+      Some((timecode(10, 2, 3, 4), false))
+    );
+    assert_eq!(
+      parse_timecode(&regex, "01:00:00;02"),
+      Some((timecode(1, 0, 0, 2), true))
+    );
+    assert_eq!(
+      parse_timecode(&regex, "01.00.59.29"),
+      Some((timecode(1, 0, 59, 29), false))
+    );
+    assert_eq!(parse_timecode(&regex, "10:00:00"), None);
+  }
+}
@@ -0,0 +1,59 @@
+use mcai_worker_sdk::JsonSchema;
+use std::time::{Duration, Instant};
+
+fn milliseconds(duration: Duration) -> f64 {
+  duration.as_secs_f64() * 1000.0
+}
+
+/// Time spent on a processed frame, in milliseconds
+#[derive(Clone, Copy, Debug, Default, JsonSchema, Serialize)]
+pub struct FrameTimings {
+  /// Decoding and filtering by the SDK since the previous processed frame, including the skipped frames
+  pub decode_and_filter_ms: f64,
+  /// Text recognition by Tesseract
+  pub ocr_ms: f64,
+}
+
+impl FrameTimings {
+  pub fn new(decode_and_filter: Duration, ocr: Duration) -> Self {
+    FrameTimings {
+      decode_and_filter_ms: milliseconds(decode_and_filter),
+      ocr_ms: milliseconds(ocr),
+    }
+  }
+}
+
+/// Timings aggregated over the job, in milliseconds
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TimingStats {
+  pub processed_frames: u32,
+  pub total_decode_and_filter_ms: f64,
+  pub total_ocr_ms: f64,
+  pub mean_ocr_ms: f64,
+  pub max_ocr_ms: f64,
+}
+
+impl TimingStats {
+  pub fn add(&mut self, timings: &FrameTimings) {
+    self.processed_frames += 1;
+    self.total_decode_and_filter_ms += timings.decode_and_filter_ms;
+    self.total_ocr_ms += timings.ocr_ms;
+    self.mean_ocr_ms = self.total_ocr_ms / self.processed_frames as f64;
+    self.max_ocr_ms = self.max_ocr_ms.max(timings.ocr_ms);
+  }
+}
+
+/// Instant at which the SDK started to decode the next frame
+#[derive(Debug, Default)]
+pub struct DecodeClock(Option<Instant>);
+
+impl DecodeClock {
+  pub fn restart(&mut self) {
+    self.0 = Some(Instant::now());
+  }
+
+  /// Time elapsed since the last restart
+  pub fn elapsed(&self) -> Duration {
+    self.0.map(|instant| instant.elapsed()).unwrap_or_default()
+  }
+}
@@ -0,0 +1,154 @@
+use crate::layout::Line;
+use crate::region::Rectangle;
+use mcai_worker_sdk::{JsonSchema, MessageError};
+use regex::Regex;
+use std::collections::HashSet;
+
+/// Term to spot in the recognised text
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+#[serde(untagged)]
+pub enum WatchlistTerm {
+  /// Word or expression, matched as whole words ignoring the case
+  Word(String),
+  /// Regular expression, matched as is
+  Pattern { pattern: String },
+}
+
+impl WatchlistTerm {
+  fn name(&self) -> &str {
+    match self {
+      WatchlistTerm::Word(word) => word,
+      WatchlistTerm::Pattern { pattern } => pattern,
+    }
+  }
+
+  fn regex(&self) -> Result<Regex, MessageError> {
+    let pattern = match self {
+      WatchlistTerm::Word(word) => format!(r"(?i)\b{}\b", regex::escape(word)),
+      WatchlistTerm::Pattern { pattern } => pattern.clone(),
+    };
+    Regex::new(&pattern).map_err(|error| {
+      MessageError::ParameterValueError(format!(
+        "Invalid watchlist term {}: {}",
+        self.name(),
+        error
+      ))
+    })
+  }
+}
+
+/// Watchlist term appearing on screen
+#[derive(Debug, Serialize)]
+pub struct Alert {
+  pub job_id: u64,
+  pub frame: u32,
+  pub pts: u64,
+  pub timestamp_ms: u64,
+  pub timecode: String,
+  /// Term of the watchlist, as given in the parameters
+  pub term: String,
+  /// Recognised text matching the term
+  pub matched_text: String,
+  /// Line containing the matched text, located in the source frame
+  pub line: String,
+  pub bounding_box: Rectangle,
+}
+
+/// Terms spotted in the recognised lines, reported when they appear
+#[derive(Debug)]
+pub struct Watchlist {
+  terms: Vec<(String, Regex)>,
+  /// Terms matched on the previous processed frame, not reported again while they stay on screen
+  on_screen: HashSet<String>,
+}
+
+impl Watchlist {
+  pub fn new(terms: &[WatchlistTerm]) -> Result<Self, MessageError> {
+    let terms = terms
+      .iter()
+      .map(|term| Ok((term.name().to_string(), term.regex()?)))
+      .collect::<Result<_, MessageError>>()?;
+
+    Ok(Watchlist {
+      terms,
+      on_screen: HashSet::new(),
+    })
+  }
+
+  /// Terms appearing in the lines of the frame, and missing from the previous processed one,
+  /// with the matched text and its line
+  pub fn spot<'a>(&mut self, lines: &'a [Line]) -> Vec<(String, String, &'a Line)> {
+    let mut spotted = vec![];
+    let mut on_screen = HashSet::new();
+
+    for (term, regex) in &self.terms {
+      for line in lines {
+        if let Some(matched) = regex.find(&line.text) {
+          if on_screen.insert(term.clone()) && !self.on_screen.contains(term) {
+            spotted.push((term.clone(), matched.as_str().to_string(), line));
+          }
+        }
+      }
+    }
+
+    self.on_screen = on_screen;
+    spotted
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::bidi::TextDirection;
+
+  fn line(text: &str) -> Line {
+    Line {
+      text: text.to_string(),
+      bounding_box: Rectangle::default(),
+      baseline: None,
+      confidence: 90.0,
+      direction: TextDirection::of(text),
+    }
+  }
+
+  fn spotted(watchlist: &mut Watchlist, texts: &[&str]) -> Vec<(String, String)> {
+    let lines: Vec<Line> = texts.iter().map(|text| line(text)).collect();
+    watchlist
+      .spot(&lines)
+      .into_iter()
+      .map(|(term, matched_text, _)| (term, matched_text))
+      .collect()
+  }
+
+  #[test]
+  fn terms_are_reported_when_they_appear() {
+    let terms: Vec<WatchlistTerm> = serde_json::from_value(serde_json::json!([
+      "Paris",
+      { "pattern": r"\d\d:\d\d" },
+    ]))
+    .unwrap();
+    let mut watchlist = Watchlist::new(&terms).unwrap();
+
+    assert_eq!(
+      spotted(&mut watchlist, &["Comparison in PARIS", "Live at 20:00"]),
+      vec![
+        ("Paris".to_string(), "PARIS".to_string()),
+        (r"\d\d:\d\d".to_string(), "20:00".to_string()),
+      ]
+    );
+    assert!(spotted(&mut watchlist, &["Comparison in PARIS", "Live at 20:00"]).is_empty());
+    assert!(spotted(&mut watchlist, &["Comparison", "Weather"]).is_empty());
+    assert_eq!(
+      spotted(&mut watchlist, &["Paris"]),
+      vec![("Paris".to_string(), "Paris".to_string())]
+    );
+  }
+
+  #[test]
+  fn invalid_patterns() {
+    let terms = [WatchlistTerm::Pattern {
+      pattern: "(".to_string(),
+    }];
+    assert!(Watchlist::new(&terms).is_err());
+  }
+}